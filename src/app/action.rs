@@ -7,7 +7,10 @@ use crate::{
 };
 
 use derivative::Derivative;
-use anyhow::Result;
+use anyhow::{
+    Result,
+    bail,
+};
 
 use crate::{
     app::{
@@ -18,12 +21,17 @@ use crate::{
     },
     content::{
         providers::ContentProvider,
+        song::Song,
         manager::action::ContentManagerAction,
         register::ID,
     },
 };
 
-pub type TypingCallback = Box<dyn FnOnce(&mut ContentProvider, String) -> ContentManagerAction + Send + Sync>;
+/// called with the typed text and the thing that asked for the typing (the loader)
+pub enum TypingCallback {
+    ContentProvider(Box<dyn FnOnce(&mut ContentProvider, String) -> ContentManagerAction + Send + Sync>),
+    Song(Box<dyn FnOnce(&mut Song, String) -> ContentManagerAction + Send + Sync>),
+}
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -104,17 +112,19 @@ impl AppAction {
                 app.typing_callback = AppAction::ApplyTyped { callback, loader }
             }
             Self::ApplyTyped {callback, loader} => {
-                match loader {
-                    ID::ContentProvider(id) => {
+                let content = app.input[..].iter().collect();
+                let action = match (loader, callback) {
+                    (ID::ContentProvider(id), TypingCallback::ContentProvider(callback)) => {
                         let cp = app.content_manager.get_provider_mut(id);
-                        let action = callback(cp, app.input[..].iter().collect());
-                        action.apply(&mut app.content_manager)?;
+                        callback(cp, content)
                     }
-                    ID::Song(id) => {
+                    (ID::Song(id), TypingCallback::Song(callback)) => {
                         let s = app.content_manager.get_song_mut(id);
-                        todo!() // FIX: this is not supported rn!!
+                        callback(s, content)
                     }
-                }
+                    (loader, _) => bail!("typing callback does not match its loader: {loader:#?}"),
+                };
+                action.apply(&mut app.content_manager)?;
             }
             Self::UpdateDisplayContent => {
                 app.browser_widget.list_builder = app.content_manager.display();
@@ -6,7 +6,13 @@ use crate::{
     error,
 };
 
-use tokio::select;
+use tokio::{
+    select,
+    signal::unix::{
+        signal,
+        SignalKind,
+    },
+};
 use tui::{
     backend::Backend,
     widgets::{
@@ -49,7 +55,10 @@ use crossterm::{
 };
 // use unicode_width::UnicodeWidthStr; // string.width() -> gives correct width (including cjk chars) (i assume)
 use anyhow::Result;
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    time::Instant,
+};
 use futures::{
     StreamExt,
     FutureExt,
@@ -62,14 +71,30 @@ use crate::{
             action::ContentManagerAction,
         },
         stack::ContentState,
+        providers::queue::Queue,
+        register::GlobalProvider,
     },
     app::{
         action::AppAction,
         display::{
             ListBuilder,
         },
+        keymap::{
+            Command,
+            KeyChord,
+            KeyLookup,
+            KEY_SEQUENCE_TIMEOUT,
+        },
+        command_line::{
+            self,
+            LineCommand,
+        },
+    },
+    service::{
+        editors::YankType,
+        notifier::Notification,
+        config::config,
     },
-    service::editors::YankType,
 };
 
 
@@ -120,25 +145,25 @@ impl BrowserWidget {
         Self::default()
     }
 
-    fn handle_events(&mut self, key: KeyEvent, ch: &mut ContentManager) -> Result<bool> {
-        match key.code {
-            KeyCode::Char('g') => {
-                todo!()
-            }
-            KeyCode::Char('G') => {
+    fn handle_command(&mut self, command: Command, ch: &mut ContentManager) -> Result<bool> {
+        match command {
+            Command::OpenMenu => {
                 ch.open_menu_for_current()?;
             }
-            KeyCode::Char('E') => {
+            Command::OpenEdit => {
                 ch.open_edit_for_current()?;
             }
-            KeyCode::Char('y') => {
+            Command::EditSelected => {
+                ch.open_edit_for_selected()?;
+            }
+            Command::ToggleYank => {
                 ch.toggle_yank_selected()?;
                 ch.increment_selection();
             }
-            KeyCode::Char('Y') => {
+            Command::ClearEditHistory => {
                 ch.edit_manager.clear().apply(ch)?;
             }
-            KeyCode::Char('X') => {
+            Command::Cut => {
                 if ch.edit_manager.yanker.is_none() {
                     ch.toggle_yank_selected()?;
                 }
@@ -148,50 +173,51 @@ impl BrowserWidget {
                     ContentManagerAction::RefreshDisplayContent.apply(ch)?;
                 }
             }
-            KeyCode::Char('C') => {
+            Command::Copy => {
                 let action = ch.edit_manager.apply_yank(YankType::Copy);
                 action.apply(ch)?;
             }
-            KeyCode::Char('v') => {
-                if let None = ch.edit_manager.yanker { // necessary as pasting things where something is yanked from, desyncs the inidex in Yanker
-                     if let ContentState::Normal = ch.content_stack.get_state() {
-                         let index = ch.get_selected_index().selected_index();
-                         let action = ch.edit_manager.try_paste(ch.content_stack.last(), Some(index));
-                         action.apply(ch)?;
-                     }
-                }
-             }
-             KeyCode::Char('V') => {
+            Command::PasteAbove | Command::PasteBelow => {
                 if let None = ch.edit_manager.yanker { // necessary as pasting things where something is yanked from, desyncs the inidex in Yanker
-                     if let ContentState::Normal = ch.content_stack.get_state() {
-                         let index = ch.get_selected_index().selected_index();
-                         let action = ch.edit_manager.try_paste(ch.content_stack.last(), Some(index+1));
-                         action.apply(ch)?;
-                     }
+                    if let ContentState::Normal = ch.content_stack.get_state() {
+                        let mut index = ch.get_selected_index().selected_index();
+                        if let Command::PasteBelow = command {
+                            index += 1;
+                        }
+                        let action = ch.edit_manager.try_paste(ch.content_stack.last(), Some(index));
+                        action.apply(ch)?;
+                    }
                 }
-             }
-            KeyCode::Char('Z') => {
+            }
+            Command::Redo => {
                 ch.edit_manager.redo_last_undo().apply(ch)?;
             }
-            KeyCode::Char('z') => {
+            Command::Notifications => {
+                ContentManagerAction::MaybePushToContentStack { id: GlobalProvider::Notifier }.apply(ch)?;
+            }
+            Command::Undo => {
                 ch.edit_manager.undo_last_edit().apply(ch)?;
             }
-            KeyCode::Esc => {
+            Command::CancelYank => {
                 match ch.edit_manager.yanker.take() {
                     Some(_) => ContentManagerAction::RefreshDisplayContent.apply(ch)?,
-                    None => return Ok(false),
+                    None => {
+                        if !ch.clear_filter()? {
+                            return Ok(false);
+                        }
+                    }
                 }
             }
-            KeyCode::Up => {
+            Command::SelectionUp => {
                 ch.decrement_selection();
             }
-            KeyCode::Down => {
+            Command::SelectionDown => {
                 ch.increment_selection();
             }
-            KeyCode::Right => {
+            Command::EnterSelected => {
                 ch.enter_selected()?;
             }
-            KeyCode::Left => {
+            Command::Back => {
                 ContentManagerAction::PopContentStack.apply(ch)?;
             }
             _ => return Ok(false),
@@ -200,7 +226,7 @@ impl BrowserWidget {
     }
 
     fn render<'a, B: Backend>(&self, f: &mut Frame<B>, r: Rect, cm: &mut ContentManager, input: &[char], input_cursor_pos: usize, state: AppState) {
-        let selected_index = cm.get_selected_index().selected_index();
+        let selected_index = cm.get_display_index().selected_index();
 
         let list = if let AppState::Typing = state {
             let pos = self.list_builder.get_abs_pos(r, selected_index);
@@ -228,7 +254,7 @@ impl BrowserWidget {
         } else {
             self.list_builder.list(r, selected_index)
         };
-        f.render_stateful_widget(list, r, cm.get_selected_index().into());
+        f.render_stateful_widget(list, r, cm.get_display_index().into());
     }
 }
 
@@ -247,21 +273,30 @@ impl PlayerWidget {
         }
     }
 
-    fn handle_events(&mut self, key: KeyEvent, ch: &mut ContentManager) -> Result<bool> {
-        match key.code {
-            KeyCode::Char('p') => {
+    fn handle_command(&mut self, command: Command, ch: &mut ContentManager) -> Result<bool> {
+        match command {
+            Command::TogglePause => {
                 ch.toggle_song_pause();
             }
-            KeyCode::Char('k') => {
-                ch.seek_song(10.0)?;
+            Command::SeekForward => {
+                ch.seek_song(config().seek_step)?;
+            }
+            Command::SeekBackward => {
+                ch.seek_song(-config().seek_step)?;
+            }
+            Command::VolumeUp => {
+                ch.change_volume(config().volume_step as i32)?;
+            }
+            Command::VolumeDown => {
+                ch.change_volume(-(config().volume_step as i32))?;
             }
-            KeyCode::Char('j') => {
-                ch.seek_song(-10.0)?;
+            Command::ToggleMute => {
+                ch.toggle_mute()?;
             }
-            KeyCode::Char('l') => {
+            Command::NextSong => {
                 ch.next_song()?;
             }
-            KeyCode::Char('h') => {
+            Command::PrevSong => {
                 ch.prev_song()?;
             }
             _ => return Ok(false),
@@ -274,12 +309,21 @@ impl PlayerWidget {
         let inner_rect = block.inner(r);
 
         if let Some(song_id) = cm.active_song {
+            let play_mode = cm.active_queue
+            .map(|id| cm.get_provider(id).as_any().downcast_ref::<Queue>())
+            .flatten()
+            .map(|q| q.play_mode);
             let song = cm.get_song(song_id).as_display();
             // TODO: center align the info
             let song_info = [
                 Some(format!("title: {title}", title = song.title())),
                 song.artist().map(|artist| format!("artist: {artist}")),
                 song.album().map(|album| format!("album: {album}")),
+                play_mode.map(|mode| format!("mode: {mode}", mode = mode.name())),
+                Some(match cm.volume.muted {
+                    true => "volume: muted".to_owned(),
+                    false => format!("volume: {}%", cm.volume.level),
+                }),
             ].into_iter()
             .filter_map(|i| i)
             .map(Span::raw)
@@ -318,12 +362,17 @@ impl PlayerWidget {
             };
 
             // render the progress bar
+            let position = cm.player.position().ok();
+            let duration = cm.player.duration().ok().filter(|&d| d > 0.0);
+            let label = format!(
+                "{} / {}",
+                position.map(format_time).unwrap_or("--:--".into()),
+                duration.map(format_time).unwrap_or("--:--".into()),
+            );
             let gauge = Gauge::default()
-            .ratio(cm.player.progress()?)
+            .ratio(cm.player.progress()?.clamp(0.0, 1.0))
             .gauge_style(Style::default().fg(Color::Cyan))
-            // .style(Style::default().fg(Color::LightGreen)) // label style
-            //? maybe show the time too? "<progress>/<duration>"
-            .label(""); // this disables the default label of percentage
+            .label(label); // this replaces the default label of percentage
             f.render_widget(gauge, song_progress_rect);
     
             // render the image
@@ -337,7 +386,6 @@ impl PlayerWidget {
 
 
 
-        // tui::widgets::LineGauge/tui::widgets::Gauge for progress bar
         match self.render_state.clone() {
             RenderState::Normal => {
                 f.render_widget(
@@ -350,9 +398,7 @@ impl PlayerWidget {
     }
 
     fn update(&mut self, ch: &mut ContentManager) -> Result<()> {
-        if ch.player.is_finished()? {
-            ch.next_song()?;
-        }
+        ch.update_playback()?;
         match &mut self.render_state {
             RenderState::Normal => {
 
@@ -362,9 +408,35 @@ impl PlayerWidget {
     }
 }
 
+/// "m:ss", or "h:mm:ss" for songs longer than an hour
+fn format_time(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
 struct StatusBar {}
 impl StatusBar {
-    fn render<B: Backend>(&self, f: &mut Frame<B>, r: Rect) {
+    fn render<B: Backend>(&self, f: &mut Frame<B>, r: Rect, toast: Option<&Notification>, command_line: Option<(char, &[char], usize)>) {
+        if let Some((prefix, input, cursor_pos)) = command_line {
+            let line = Paragraph::new(Spans::from(Span::raw(format!("{prefix}{}", input.iter().collect::<String>()))))
+                .alignment(Alignment::Left)
+                .style(Style::default().bg(Color::Black));
+            f.render_widget(line, r);
+            f.set_cursor(r.x + 1 + cursor_pos as u16, r.y);
+            return;
+        }
+        if let Some(notif) = toast {
+            let toast = Paragraph::new(Spans::from(Span::styled(notif.text(), notif.severity.style())))
+                .alignment(Alignment::Left)
+                .style(Style::default().bg(Color::Black));
+            f.render_widget(toast, r);
+            return;
+        }
         let (msg, style) = (vec![
             Spans::from(vec![
                 Span::raw("Press "),
@@ -392,6 +464,10 @@ pub enum AppState {
     Quit,
     Typing,
     DbgInput,
+    /// typing a command after ':'
+    Command,
+    /// typing the filter for the current provider after '/'
+    Filter,
 }
 
 pub struct App {
@@ -408,13 +484,23 @@ pub struct App {
     // handles all ui from the player widget side
     player_widget: PlayerWidget,
 
+    /// keys of a partially typed key sequence, and when the last one was pressed
+    pending_keys: Vec<KeyChord>,
+    pending_keys_time: Instant,
+    /// completions of the command line and the one that is currently shown
+    completion: Option<(Vec<String>, usize)>,
+
     pub content_manager: ContentManager,
     pub redraw_needed: bool,
 }
 
 impl App {
     pub fn load() -> Result<Self> {
-        let a = Self {
+        let content_manager = match ContentManager::try_load()? {
+            Some(ch) => ch,
+            None => ContentManager::new()?,
+        };
+        let mut a = Self {
             input: Default::default(),
             input_cursor_pos: 0,
             state: AppState::Browser,
@@ -424,10 +510,16 @@ impl App {
             browser_widget: BrowserWidget::new(),
             player_widget: PlayerWidget::new(),
 
-            content_manager: ContentManager::try_load()?
-            .unwrap_or(ContentManager::new()?),
+            pending_keys: Default::default(),
+            pending_keys_time: Instant::now(),
+            completion: None,
+
+            content_manager,
             redraw_needed: false,
         };
+        if let Err(err) = a.content_manager.resume_playback() {
+            a.content_manager.notify_error(format!("could not resume the last song: {err}"));
+        }
         Ok(a)
     }
 
@@ -435,18 +527,31 @@ impl App {
         AppAction::UpdateDisplayContent.apply(self)?;
         terminal.draw(|f| self.render(f).unwrap())?;
         let mut reader = EventStream::new();
+        // redraws the player (time, progress bar) even if nothing else happens
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs_f64(0.5));
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        // these quit normally, so the db gets saved
+        let mut sigterm = signal(SignalKind::terminate())?;
+        let mut sighup = signal(SignalKind::hangup())?;
+        let mut sigint = signal(SignalKind::interrupt())?;
         #[cfg(feature = "sixel")]
         let _ = crate::image::printer::sixel::is_sixel_supported(); // for some reason this does not behave well when tokio does its stuff (maybe cus Write on stdout). so cache it (lazy_static)
         loop {
             let event = reader.next().fuse();
             let action = self.content_manager.parallel_handle.recv();
             let app_action = self.content_manager.app_action_receiver.recv();
-            let sleep = tokio::time::sleep(std::time::Duration::from_secs_f64(0.5));
             select! {
                 ev = event => self.handle_events(ev.unwrap()?)?,
-                action = action => action.apply(&mut self.content_manager)?,
+                action = action => {
+                    if let Err(err) = action.apply(&mut self.content_manager) {
+                        self.content_manager.notify_error(format!("{err}"));
+                    }
+                }
                 app_action = app_action => app_action.unwrap().apply(self)?,
-                _ = sleep => (),
+                _ = tick.tick() => (),
+                _ = sigterm.recv() => return Ok(()),
+                _ = sighup.recv() => return Ok(()),
+                _ = sigint.recv() => return Ok(()),
             }
             let _ = self.content_manager.app_action_receiver // to make sure not to render without updating content
             .try_recv()
@@ -467,6 +572,9 @@ impl App {
 
     fn update(&mut self) -> Result<()> {
         self.player_widget.update(&mut self.content_manager)?;
+        if let Err(err) = self.content_manager.maybe_autosave() {
+            self.content_manager.notify_error(format!("autosave failed: {err}"));
+        }
         Ok(())
     }
     
@@ -475,57 +583,78 @@ impl App {
             Event::Key(key) => {
                 let event_handled = match self.state {
                     AppState::Typing => {
-                        let mut event_handled = true;
                         match key.code {
                             KeyCode::Esc => {
                                 self.state = AppState::Browser; // TODO: should this be a stack too?
                                 ContentManagerAction::PopContentStack.apply(&mut self.content_manager)?;
+                                true
                             }
-                            KeyCode::Char(c) => {
-                                self.input.insert(self.input_cursor_pos, c);
-                                self.input_cursor_pos += 1;
+                            KeyCode::Enter => {
+                                let action = std::mem::replace(&mut self.typing_callback, AppAction::None);
+                                action.apply(self)?;
+                                self.state = AppState::Browser;
+                                true
                             }
-                            KeyCode::Backspace => {
-                                if self.input_cursor_pos > 0 {
-                                    self.input_cursor_pos -= 1;
-                                    self.input.remove(self.input_cursor_pos);
-                                }
+                            _ => self.edit_input(key),
+                        }
+                    }
+                    AppState::Command => {
+                        match key.code {
+                            KeyCode::Esc => {
+                                self.state = AppState::Browser;
+                                self.completion = None;
+                                true
                             }
-                            KeyCode::Left => {
-                                match key.modifiers { // these are bitfields, not enum variants
-                                    KeyModifiers::NONE => {
-                                        if self.input_cursor_pos > 0 {
-                                            self.input_cursor_pos -= 1;
-                                        }
-                                    }
-                                    // KeyModifiers::CONTROL | KeyModifiers::SHIFT => {}
-                                    _ => event_handled = false,
-                                }
+                            KeyCode::Tab => {
+                                self.complete_command();
+                                true
                             }
-                            KeyCode::Right => {
-                                match key.modifiers {
-                                    KeyModifiers::NONE => {
-                                        if self.input_cursor_pos < self.input.len() {
-                                            self.input_cursor_pos += 1;
-                                        }
+                            KeyCode::Enter => {
+                                self.state = AppState::Browser;
+                                self.completion = None;
+                                let line = self.input.drain(..).collect::<String>();
+                                self.input_cursor_pos = 0;
+                                if !line.trim().is_empty() {
+                                    if let Err(err) = LineCommand::parse(&line).and_then(|c| c.apply(self)) {
+                                        self.content_manager.notify_error(format!("{err}"));
                                     }
-                                    _ => event_handled = false,
                                 }
+                                true
                             }
-                            KeyCode::Home => {
-                                self.input_cursor_pos = 0;
+                            _ => {
+                                self.completion = None;
+                                self.edit_input(key)
                             }
-                            KeyCode::End => {
-                                self.input_cursor_pos = self.input.len();
+                        }
+                    }
+                    AppState::Filter => {
+                        match key.code {
+                            KeyCode::Esc => {
+                                self.state = AppState::Browser;
+                                self.content_manager.clear_filter()?;
+                                true
                             }
                             KeyCode::Enter => {
-                                let action = std::mem::replace(&mut self.typing_callback, AppAction::None);
-                                action.apply(self)?;
                                 self.state = AppState::Browser;
+                                true
+                            }
+                            KeyCode::Up => {
+                                self.content_manager.decrement_selection();
+                                true
+                            }
+                            KeyCode::Down => {
+                                self.content_manager.increment_selection();
+                                true
+                            }
+                            _ => {
+                                let handled = self.edit_input(key);
+                                if handled {
+                                    let query = self.input.iter().collect();
+                                    self.content_manager.set_filter_query(query)?;
+                                }
+                                handled
                             }
-                            _ => event_handled = false,
                         }
-                        event_handled
                     }
                     AppState::DbgInput => {
                         match key.code {
@@ -541,30 +670,30 @@ impl App {
                         }
                     }
                     _ => {
-                        let mut event_handled = false;
-                        if !event_handled {
-                            event_handled = self.browser_widget.handle_events(key, &mut self.content_manager)?;
-                            // if event_handled {
-                            //     self.browser_widget.update(&mut self.content_manager);
-                            // }
+                        if self.pending_keys_time.elapsed() > KEY_SEQUENCE_TIMEOUT {
+                            self.pending_keys.clear();
                         }
-                        if !event_handled {event_handled = self.player_widget.handle_events(key, &mut self.content_manager)?;}
-                        event_handled
+                        self.pending_keys_time = Instant::now();
+                        self.pending_keys.push(key.into());
+                        match config().keymap.lookup(&self.pending_keys) {
+                            KeyLookup::Command(command) => {
+                                self.pending_keys.clear();
+                                self.handle_command(command)?;
+                            }
+                            KeyLookup::Pending => (),
+                            KeyLookup::None => {
+                                // the last key might still start a binding of its own
+                                let retry = self.pending_keys.len() > 1;
+                                self.pending_keys.clear();
+                                if retry {
+                                    return self.handle_events(Event::Key(key));
+                                }
+                            }
+                        }
+                        true
                     },
                 };
                 if event_handled {return Ok(())}
-            
-                match self.state {
-                    _ => match key.code {
-                        KeyCode::Char('q') => {
-                            self.state = AppState::Quit;
-                        }
-                        KeyCode::Char('d') => {
-                            self.state = AppState::DbgInput;
-                        }
-                        _ => ()
-                    }
-                }    
             }
             Event::Resize(_, _) => {
                 self.content_manager.image_handler.dimensions_changed();
@@ -574,6 +703,103 @@ impl App {
         Ok(())
     }
     
+    /// cursor movement and editing of the input line
+    fn edit_input(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) => {
+                self.input.insert(self.input_cursor_pos, c);
+                self.input_cursor_pos += 1;
+            }
+            KeyCode::Backspace => {
+                if self.input_cursor_pos > 0 {
+                    self.input_cursor_pos -= 1;
+                    self.input.remove(self.input_cursor_pos);
+                }
+            }
+            KeyCode::Left => {
+                match key.modifiers { // these are bitfields, not enum variants
+                    KeyModifiers::NONE => {
+                        if self.input_cursor_pos > 0 {
+                            self.input_cursor_pos -= 1;
+                        }
+                    }
+                    // KeyModifiers::CONTROL | KeyModifiers::SHIFT => {}
+                    _ => return false,
+                }
+            }
+            KeyCode::Right => {
+                match key.modifiers {
+                    KeyModifiers::NONE => {
+                        if self.input_cursor_pos < self.input.len() {
+                            self.input_cursor_pos += 1;
+                        }
+                    }
+                    _ => return false,
+                }
+            }
+            KeyCode::Home => {
+                self.input_cursor_pos = 0;
+            }
+            KeyCode::End => {
+                self.input_cursor_pos = self.input.len();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// each tab moves to the next completion of the line
+    fn complete_command(&mut self) {
+        let (candidates, i) = match self.completion.take() {
+            Some((candidates, i)) => {
+                let i = (i+1) % candidates.len();
+                (candidates, i)
+            }
+            None => {
+                let line = self.input.iter().collect::<String>();
+                (command_line::completions(&line, &self.content_manager), 0)
+            }
+        };
+        if let Some(line) = candidates.get(i) {
+            self.input = line.chars().collect();
+            self.input_cursor_pos = self.input.len();
+            self.completion = Some((candidates, i));
+        }
+    }
+
+    fn handle_command(&mut self, command: Command) -> Result<()> {
+        let mut handled = self.browser_widget.handle_command(command, &mut self.content_manager)?;
+        if !handled {
+            handled = self.player_widget.handle_command(command, &mut self.content_manager)?;
+        }
+        if handled {
+            return Ok(());
+        }
+        match command {
+            Command::Quit => {
+                self.state = AppState::Quit;
+            }
+            Command::DebugInput => {
+                self.state = AppState::DbgInput;
+            }
+            Command::CommandLine => {
+                self.state = AppState::Command;
+                self.input.clear();
+                self.input_cursor_pos = 0;
+            }
+            Command::Filter => {
+                if let Some(query) = self.content_manager.start_filter() {
+                    self.state = AppState::Filter;
+                    self.input = query.chars().collect();
+                    self.input_cursor_pos = self.input.len();
+                    ContentManagerAction::RefreshDisplayContent.apply(&mut self.content_manager)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
     fn render<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<()> {
         let (status_rect, right_rect, left_rect) = {
             let mut chunks = Layout::default()
@@ -594,7 +820,12 @@ impl App {
             (chunks.pop().unwrap(), lower_chunks.pop().unwrap(), lower_chunks.pop().unwrap())
         };
 
-        self.status_bar.render(f, status_rect);
+        let command_line = match self.state {
+            AppState::Command => Some((':', &self.input[..], self.input_cursor_pos)),
+            AppState::Filter => Some(('/', &self.input[..], self.input_cursor_pos)),
+            _ => None,
+        };
+        self.status_bar.render(f, status_rect, self.content_manager.notifier.toast(), command_line);
         self.player_widget.render(f, right_rect, &mut self.content_manager)?;
         self.browser_widget.render(f, left_rect, &mut self.content_manager, &self.input, self.input_cursor_pos, self.state);
         
@@ -0,0 +1,508 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use anyhow::{
+    Result,
+    bail,
+    Context,
+};
+
+use crate::{
+    app::app::{
+        App,
+        AppState,
+    },
+    content::{
+        manager::{
+            manager::ContentManager,
+            action::ContentManagerAction,
+        },
+        providers::{
+            queue::{
+                Queue,
+                PlayMode,
+            },
+            yt_explorer::{
+                self,
+                YTSearchFilter,
+            },
+            search_results,
+            downloads,
+            traits::CPProvider,
+        },
+        register::{
+            ContentProviderID,
+            SongID,
+            GlobalProvider,
+            ID,
+        },
+        stack::ContentState,
+        song::tagged_file_song::TagField,
+    },
+    service::{
+        editors::{
+            Yank,
+            YankAction,
+        },
+        notifier::Severity,
+        tag_editor::{
+            self,
+            TagOp,
+        },
+    },
+};
+
+/// names of the commands that can be typed after ':'
+const COMMANDS: &[&str] = &[
+    "play",
+    "pause",
+    "next",
+    "prev",
+    "seek",
+    "volume",
+    "mute",
+    "queue",
+    "search",
+    "save",
+    "goto",
+    "mode",
+    "tag",
+    "download",
+    "downloads",
+    "quit",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineCommand {
+    /// play the selected song or the selected song provider
+    Play,
+    Pause,
+    Next,
+    Prev,
+    Seek(Seek),
+    Volume(Volume),
+    /// toggle mute
+    Mute,
+    /// add the selected songs to the end of the active queue
+    QueueAdd,
+    /// search every song and provider that is already in the library
+    Search {
+        query: String,
+    },
+    SearchYT {
+        filter: YTSearchFilter,
+        query: String,
+    },
+    Save,
+    /// go to a provider in main by its name
+    Goto(String),
+    Mode(PlayMode),
+    /// preview a tag edit of the yanked songs
+    Tag(TagOp),
+    /// download the selected online song (or the online songs of the selected provider) into the music dir
+    Download,
+    /// show the downloads of this session
+    Downloads,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Volume {
+    By(i32),
+    To(i32),
+}
+impl Volume {
+    /// "+5", "-5", "80" (in percent)
+    fn parse(s: &str) -> Result<Self> {
+        let usage = || format!("invalid volume '{s}'. usage: volume [+|-]<percent>");
+        let volume = match s.chars().next() {
+            Some('+') => Self::By(s[1..].parse().ok().with_context(usage)?),
+            Some('-') => Self::By(-s[1..].parse::<i32>().ok().with_context(usage)?),
+            _ => Self::To(s.parse().ok().with_context(usage)?),
+        };
+        Ok(volume)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Seek {
+    By(f64),
+    To(f64),
+}
+impl Seek {
+    /// "+10", "-10", "90", "1:30"
+    fn parse(s: &str) -> Result<Self> {
+        let (sign, t) = match s.chars().next() {
+            Some('+') => (Some(1.0), &s[1..]),
+            Some('-') => (Some(-1.0), &s[1..]),
+            _ => (None, s),
+        };
+        let secs = t.split(':')
+        .try_fold(0.0, |acc, part| {
+            part.parse::<f64>()
+            .ok()
+            .filter(|p| *p >= 0.0)
+            .map(|p| acc * 60.0 + p)
+        })
+        .with_context(|| format!("invalid time '{s}'. usage: seek [+|-]<seconds|mm:ss>"))?;
+        let seek = match sign {
+            Some(sign) => Self::By(sign * secs),
+            None => Self::To(secs),
+        };
+        Ok(seek)
+    }
+}
+
+impl LineCommand {
+    pub fn parse(line: &str) -> Result<Self> {
+        let (command, rest) = split_word(line);
+        let command = match command {
+            "play" => Self::Play,
+            "pause" => Self::Pause,
+            "next" => Self::Next,
+            "prev" => Self::Prev,
+            "seek" => Self::Seek(Seek::parse(rest)?),
+            "volume" => Self::Volume(Volume::parse(rest)?),
+            "mute" => Self::Mute,
+            "queue" => {
+                match split_word(rest) {
+                    ("add", "") => Self::QueueAdd,
+                    _ => bail!("usage: queue add"),
+                }
+            }
+            "search" => {
+                let (source, query) = split_word(rest);
+                match source {
+                    "yt" => {
+                        let (first, remaining) = split_word(query);
+                        let (filter, query) = match YTSearchFilter::from_name(first) {
+                            Some(filter) if !remaining.is_empty() => (filter, remaining),
+                            _ => (YTSearchFilter::Songs, query),
+                        };
+                        if query.is_empty() {
+                            bail!("usage: search yt [albums|songs|videos|playlists] <query>");
+                        }
+                        Self::SearchYT { filter, query: query.to_owned() }
+                    }
+                    "" => bail!("usage: search [yt] <query>"),
+                    _ => Self::Search { query: rest.to_owned() },
+                }
+            }
+            "save" => Self::Save,
+            "goto" => {
+                if rest.is_empty() {
+                    bail!("usage: goto <provider name>");
+                }
+                Self::Goto(rest.to_owned())
+            }
+            "mode" => {
+                let mode = PlayMode::iter()
+                .iter()
+                .cloned()
+                .find(|m| command_name(m.name()) == rest)
+                .with_context(|| format!("unknown play mode '{rest}'"))?;
+                Self::Mode(mode)
+            }
+            "tag" => Self::Tag(parse_tag_op(rest)?),
+            "download" => Self::Download,
+            "downloads" => Self::Downloads,
+            "quit" | "q" => Self::Quit,
+            c => bail!("unknown command '{c}'"),
+        };
+        Ok(command)
+    }
+
+    pub fn apply(self, app: &mut App) -> Result<()> {
+        let ch = &mut app.content_manager;
+        match self {
+            Self::Play => {
+                match selection(ch) {
+                    Some(Selection::Song { provider, song }) => {
+                        ch.play_song(song)?;
+                        ch.set_queue(provider, song);
+                    }
+                    Some(Selection::Provider(id)) => {
+                        let first = ch.get_provider(id)
+                        .as_song_provider()
+                        .map(|p| p.songs().next().cloned())
+                        .flatten();
+                        match first {
+                            Some(song) => {
+                                ch.play_song(song)?;
+                                ch.set_queue(id, song);
+                            }
+                            None => ch.notify(Severity::Warning, "nothing to play"),
+                        }
+                    }
+                    None => ch.notify(Severity::Warning, "nothing to play"),
+                }
+                ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+            }
+            Self::Pause => {
+                ch.toggle_song_pause();
+            }
+            Self::Next => {
+                ch.next_song()?;
+            }
+            Self::Prev => {
+                ch.prev_song()?;
+            }
+            Self::Seek(seek) => {
+                match seek {
+                    Seek::By(t) => ch.seek_song(t)?,
+                    Seek::To(t) => ch.seek_song_to(t)?,
+                }
+            }
+            Self::Volume(volume) => {
+                match volume {
+                    Volume::By(v) => ch.change_volume(v)?,
+                    Volume::To(v) => ch.set_volume(v)?,
+                }
+            }
+            Self::Mute => {
+                ch.toggle_mute()?;
+            }
+            Self::QueueAdd => {
+                let q_id = match ch.active_queue {
+                    Some(id) => id,
+                    None => {
+                        ch.notify(Severity::Warning, "no active queue to add to");
+                        return Ok(());
+                    }
+                };
+                let songs = match selection(ch) {
+                    Some(Selection::Song { song, .. }) => vec![song],
+                    Some(Selection::Provider(id)) => {
+                        ch.get_provider(id)
+                        .as_song_provider()
+                        .map(|p| p.songs().cloned().collect())
+                        .unwrap_or_default()
+                    }
+                    None => vec![],
+                };
+                if songs.is_empty() {
+                    ch.notify(Severity::Warning, "no songs selected");
+                    return Ok(());
+                }
+                // pasted like the paste keys do, so it can be undone
+                let num_songs = songs.len();
+                let items = songs.into_iter()
+                .enumerate()
+                .map(|(index, item)| Yank { item, index })
+                .collect::<Vec<_>>();
+                let action = YankAction::TryPasteIntoProvider {
+                    yank: items.into(),
+                    yanked_to: q_id,
+                    paste_pos: None,
+                };
+                action.apply(ch)?;
+                ch.notify(Severity::Info, format!("added {num_songs} songs to the queue"));
+            }
+            Self::Search { query } => {
+                search_results::search_action(query).apply(ch)?;
+            }
+            Self::SearchYT { filter, query } => {
+                yt_explorer::search_action(query, filter).apply(ch)?;
+            }
+            Self::Save => {
+                ch.save_now()?;
+                ch.notify(Severity::Info, "saved");
+            }
+            Self::Goto(name) => {
+                goto(ch, &name)?;
+            }
+            Self::Mode(mode) => {
+                let q_id = match ch.active_queue {
+                    Some(id) => id,
+                    None => {
+                        ch.notify(Severity::Warning, "no active queue");
+                        return Ok(());
+                    }
+                };
+                ch.get_provider_mut(q_id)
+                .as_any_mut()
+                .downcast_mut::<Queue>()
+                .unwrap()
+                .set_play_mode(mode);
+                ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+            }
+            Self::Tag(op) => {
+                tag_editor::preview_action(op).apply(ch)?;
+            }
+            Self::Download => {
+                let songs = match selection(ch) {
+                    Some(Selection::Song { song, .. }) => vec![song],
+                    Some(Selection::Provider(id)) => {
+                        ch.get_provider(id)
+                        .as_song_provider()
+                        .map(|p| p.songs().cloned().collect())
+                        .unwrap_or_default()
+                    }
+                    None => vec![],
+                };
+                downloads::download_action(songs).apply(ch)?;
+            }
+            Self::Downloads => {
+                let id = ch.get_or_add_downloads();
+                ContentManagerAction::PushToContentStack { id: id.into() }.apply(ch)?;
+                ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+            }
+            Self::Quit => {
+                app.state = AppState::Quit;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// every way the line can be completed, as whole lines
+pub fn completions(line: &str, ch: &ContentManager) -> Vec<String> {
+    let (command, rest) = split_word(line);
+    let (done, partial) = if !line.trim_start().contains(char::is_whitespace) {
+        ("", line.trim_start())
+    } else if command == "goto" {
+        // provider names can have spaces in them
+        line.split_at(line.len() - rest.len())
+    } else {
+        match line.rfind(char::is_whitespace) {
+            Some(i) => line.split_at(i+1),
+            None => ("", line),
+        }
+    };
+    let words = done.split_whitespace().collect::<Vec<_>>();
+
+    let candidates: Vec<String> = match words.as_slice() {
+        [] => COMMANDS.iter().map(|c| c.to_string()).collect(),
+        ["queue"] => vec!["add".into()],
+        ["search"] => vec!["yt".into()],
+        ["search", "yt"] => YTSearchFilter::iter().iter().map(|f| f.ytmusic_filter().to_owned()).collect(),
+        ["mode"] => PlayMode::iter().iter().map(|m| command_name(m.name())).collect(),
+        ["goto"] => goto_targets(ch),
+        ["tag"] => vec!["set".into(), "renumber".into(), "replace".into()],
+        ["tag", "set" | "replace"] => TagField::iter().iter().map(TagField::name).collect(),
+        _ => vec![],
+    };
+    let partial = partial.to_lowercase();
+    candidates
+    .into_iter()
+    .filter(|c| c.to_lowercase().starts_with(&partial))
+    .map(|c| format!("{done}{c}"))
+    .collect()
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    match s.find(char::is_whitespace) {
+        Some(i) => (&s[..i], s[i..].trim_start()),
+        None => (s, ""),
+    }
+}
+
+const TAG_USAGE: &str = "usage: tag set <field> [value] | tag renumber | tag replace <field> /<regex>/<replacement>/";
+
+/// "set album Some Album", "renumber", "replace title /\s*\(Official Video\)//".
+/// any char can be used instead of '/' in replace
+fn parse_tag_op(s: &str) -> Result<TagOp> {
+    let (op, rest) = split_word(s);
+    let (field, value) = split_word(rest);
+    let parse_field = |field: &str| TagField::from_name(field).with_context(|| format!("unknown tag '{field}'. {TAG_USAGE}"));
+    let op = match op {
+        "set" => TagOp::Set {
+            field: parse_field(field)?,
+            value: Some(value.to_owned()).filter(|v| !v.is_empty()),
+        },
+        "renumber" => TagOp::Renumber,
+        "replace" => {
+            let field = parse_field(field)?;
+            let delimiter = value.chars().next().context(TAG_USAGE)?;
+            let parts = value[delimiter.len_utf8()..].split(delimiter).collect::<Vec<_>>();
+            let (pattern, with) = match parts.as_slice() {
+                [pattern, with] | [pattern, with, ""] => (pattern.to_string(), with.to_string()),
+                _ => bail!(TAG_USAGE),
+            };
+            regex::Regex::new(&pattern)?;
+            TagOp::Replace { field, pattern, with }
+        }
+        _ => bail!(TAG_USAGE),
+    };
+    Ok(op)
+}
+
+/// "repeat all" -> "repeat_all"
+fn command_name(name: &str) -> String {
+    name.replace(" ", "_")
+}
+
+enum Selection {
+    Song {
+        provider: ContentProviderID,
+        song: SongID,
+    },
+    Provider(ContentProviderID),
+}
+
+/// the item under the cursor in the current provider. None if the filter hides it
+fn selection(ch: &ContentManager) -> Option<Selection> {
+    if !matches!(ch.content_stack.get_state(), ContentState::Normal) {
+        return None;
+    }
+    let id = match ch.content_stack.last() {
+        GlobalProvider::ContentProvider(id) => id,
+        GlobalProvider::Notifier => return None,
+    };
+    let cp = ch.get_provider(id);
+    if cp.get_size() == 0 || ch.selection_filtered_out(id) {
+        return None;
+    }
+    let selection = match cp.get_selected() {
+        ID::Song(song) => Selection::Song { provider: id, song },
+        ID::ContentProvider(id) => Selection::Provider(id),
+    };
+    Some(selection)
+}
+
+fn goto_targets(ch: &ContentManager) -> Vec<String> {
+    ["main".to_owned(), "notifications".to_owned()]
+    .into_iter()
+    .chain(
+        ch.get_main_provider()
+        .providers()
+        .map(|&id| ch.get_provider(id).as_display().get_name().into_owned())
+    )
+    .collect()
+}
+
+/// clears the content stack till main and opens the provider with the given name (ignoring case)
+fn goto(ch: &mut ContentManager, name: &str) -> Result<()> {
+    let name = name.to_lowercase();
+    let dest = match name.as_str() {
+        "main" => None,
+        "notifications" => Some(GlobalProvider::Notifier),
+        _ => {
+            let names = ch.get_main_provider()
+            .providers()
+            .map(|&id| (id, ch.get_provider(id).as_display().get_name().to_lowercase()))
+            .collect::<Vec<_>>();
+            let id = names.iter()
+            .find(|(_, n)| *n == name)
+            .or(names.iter().find(|(_, n)| n.starts_with(&name)))
+            .map(|(id, _)| *id)
+            .with_context(|| format!("no provider named '{name}'"))?;
+            Some(id.into())
+        }
+    };
+
+    ch.content_stack.set_state_normal();
+    while ch.content_stack.len() > 1 {
+        ContentManagerAction::PopContentStack.apply(ch)?;
+    }
+    if let Some(id) = dest {
+        ContentManagerAction::PushToContentStack { id }.apply(ch)?;
+    }
+    ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+    Ok(())
+}
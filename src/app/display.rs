@@ -114,6 +114,12 @@ impl<'a> ListBuilder<'a> {
         self.block(block);
         self
     }
+    /// adds more text after the title
+    pub fn extend_title<T: Into<Span<'a>>>(&mut self, span: T) -> &mut Self {
+        let mut title = self.title.take().unwrap_or_default();
+        title.0.push(span.into());
+        self.title(title)
+    }
     pub fn block<'b: 'c + 'a, 'c>(&'c mut self, block: Block<'b>) -> &mut Self {
         if self.title.is_some() {
             let title = self.title
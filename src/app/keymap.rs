@@ -0,0 +1,290 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::{
+    collections::HashMap,
+    time::Duration,
+};
+use anyhow::{
+    Result,
+    bail,
+    Context,
+};
+use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// everything that can be bound to a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    // browser
+    SelectionUp,
+    SelectionDown,
+    EnterSelected,
+    Back,
+    OpenMenu,
+    OpenEdit,
+    EditSelected,
+    ToggleYank,
+    CancelYank,
+    ClearEditHistory,
+    Cut,
+    Copy,
+    PasteAbove,
+    PasteBelow,
+    Undo,
+    Redo,
+    Notifications,
+
+    // player
+    TogglePause,
+    SeekForward,
+    SeekBackward,
+    VolumeUp,
+    VolumeDown,
+    ToggleMute,
+    NextSong,
+    PrevSong,
+
+    // app
+    Quit,
+    DebugInput,
+    CommandLine,
+    Filter,
+}
+
+/// a single key press with its modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+impl KeyChord {
+    /// shift is already a part of chars ('G' is shift + 'g'), so it is ignored for them.
+    /// KeyChord::parse turns "shift-g" into 'G' before this
+    fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        if let KeyCode::Char(_) = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    /// parses things like "G", "ctrl-x", "alt-shift-up", "space", "f5". "shift-g" is the same as "G"
+    pub fn parse(s: &str) -> Result<Self> {
+        let (mods, key) = if s.chars().count() == 1 {
+            ("", s)
+        } else if let Some(mods) = s.strip_suffix("--") {
+            (mods, "-")
+        } else {
+            match s.rsplit_once('-') {
+                Some((mods, key)) => (mods, key),
+                None => ("", s),
+            }
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods.split('-').filter(|m| !m.is_empty()) {
+            modifiers |= match m.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier '{m}' in key '{s}'"),
+            };
+        }
+
+        let code = if key.chars().count() == 1 {
+            KeyCode::Char(key.chars().next().unwrap())
+        } else {
+            match key.to_lowercase().as_str() {
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                k => {
+                    let n = k.strip_prefix('f')
+                    .map(|n| n.parse::<u8>().ok())
+                    .flatten()
+                    .with_context(|| format!("unknown key '{key}' in '{s}'"))?;
+                    KeyCode::F(n)
+                }
+            }
+        };
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                if c.is_uppercase() {
+                    KeyCode::Char(c)
+                } else if c.is_lowercase() && c.to_uppercase().count() == 1 {
+                    KeyCode::Char(c.to_uppercase().next().unwrap())
+                } else {
+                    bail!("shift can't be used with '{c}' in '{s}'. use the shifted char itself");
+                }
+            }
+            code => code,
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+/// parses space seperated chords like "g g" or "ctrl-w j"
+fn parse_sequence(s: &str) -> Result<Vec<KeyChord>> {
+    let keys = s.split_whitespace()
+    .map(KeyChord::parse)
+    .collect::<Result<Vec<_>>>()?;
+    if keys.is_empty() {
+        bail!("empty key binding");
+    }
+    Ok(keys)
+}
+
+/// the keys of a partially typed sequence are dropped if the next key takes longer than this
+pub const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// the opposite of parse_sequence, for error messages
+fn describe_sequence(keys: &[KeyChord]) -> String {
+    keys.iter()
+    .map(|k| {
+        let mut s = String::new();
+        for (m, name) in [(KeyModifiers::CONTROL, "ctrl-"), (KeyModifiers::ALT, "alt-"), (KeyModifiers::SHIFT, "shift-")] {
+            if k.modifiers.contains(m) {
+                s.push_str(name);
+            }
+        }
+        match k.code {
+            KeyCode::Char(' ') => s.push_str("space"),
+            KeyCode::Char(c) => s.push(c),
+            KeyCode::F(n) => s.push_str(&format!("f{n}")),
+            code => s.push_str(&format!("{code:?}").to_lowercase()),
+        }
+        s
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+pub enum KeyLookup {
+    Command(Command),
+    /// the keys pressed so far are the start of some binding
+    Pending,
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Vec<KeyChord>, Command>,
+}
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = [
+            ("up", Command::SelectionUp),
+            ("down", Command::SelectionDown),
+            ("right", Command::EnterSelected),
+            ("left", Command::Back),
+            ("G", Command::OpenMenu),
+            ("E", Command::OpenEdit),
+            ("e", Command::EditSelected),
+            ("y", Command::ToggleYank),
+            ("esc", Command::CancelYank),
+            ("Y", Command::ClearEditHistory),
+            ("X", Command::Cut),
+            ("C", Command::Copy),
+            ("v", Command::PasteAbove),
+            ("V", Command::PasteBelow),
+            ("z", Command::Undo),
+            ("Z", Command::Redo),
+            ("N", Command::Notifications),
+
+            ("p", Command::TogglePause),
+            ("k", Command::SeekForward),
+            ("j", Command::SeekBackward),
+            ("+", Command::VolumeUp),
+            ("-", Command::VolumeDown),
+            ("m", Command::ToggleMute),
+            ("l", Command::NextSong),
+            ("h", Command::PrevSong),
+
+            ("q", Command::Quit),
+            ("d", Command::DebugInput),
+            (":", Command::CommandLine),
+            ("/", Command::Filter),
+        ].into_iter()
+        .map(|(k, c)| (parse_sequence(k).unwrap(), c))
+        .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// user bindings are added on top of the defaults. a binding can be removed by binding it to "none"
+    pub fn with_bindings(bindings: HashMap<String, String>) -> Result<Self> {
+        let mut keymap = Self::default();
+        for (keys, command) in bindings {
+            let keys = parse_sequence(&keys)
+            .with_context(|| format!("invalid key binding '{keys}'"))?;
+            if command == "none" {
+                keymap.bindings.remove(&keys);
+                continue;
+            }
+            let command = toml::Value::String(command.clone())
+            .try_into::<Command>()
+            .with_context(|| format!("unknown command '{command}'"))?;
+            keymap.bindings.insert(keys, command);
+        }
+        keymap.check_shadowed()?;
+        Ok(keymap)
+    }
+
+    /// a binding that is the start of a longer one would always run before the longer one could be typed
+    fn check_shadowed(&self) -> Result<()> {
+        for keys in self.bindings.keys() {
+            let longer = self.bindings.keys().find(|k| k.len() > keys.len() && k.starts_with(keys));
+            if let Some(longer) = longer {
+                bail!(
+                    "key binding '{}' ({:?}) shadows '{}' ({:?}). bind one of them to \"none\"",
+                    describe_sequence(keys),
+                    self.bindings[keys],
+                    describe_sequence(longer),
+                    self.bindings[longer],
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn lookup(&self, keys: &[KeyChord]) -> KeyLookup {
+        if let Some(&command) = self.bindings.get(keys) {
+            return KeyLookup::Command(command);
+        }
+        if self.bindings.keys().any(|k| k.len() > keys.len() && k.starts_with(keys)) {
+            return KeyLookup::Pending;
+        }
+        KeyLookup::None
+    }
+}
@@ -2,4 +2,5 @@ pub mod run;
 pub mod app;
 pub mod action;
 pub mod display;
-
+pub mod keymap;
+pub mod command_line;
@@ -13,6 +13,7 @@ use std::{
         set_hook,
         take_hook,
     },
+    path::PathBuf,
 };
 use tui::{
     backend::{
@@ -34,20 +35,89 @@ use crossterm::{
         LeaveAlternateScreen,
     },
 };
-use anyhow::Result;
+use anyhow::{
+    Result,
+    bail,
+};
 
 use crate::{
     app::app::App,
-    service::log::init_logger,
+    service::{
+        log::init_logger,
+        config::{
+            init_config,
+            write_default_config,
+        },
+        db::storage,
+    },
 };
 
+#[derive(Debug, Default)]
+struct Args {
+    config_path: Option<PathBuf>,
+    /// Some(None) writes to the default path
+    write_default_config: Option<Option<PathBuf>>,
+    convert_db: Option<(PathBuf, PathBuf)>,
+}
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Self::default();
+        let mut iter = std::env::args().skip(1).peekable();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--config" => {
+                    match iter.next() {
+                        Some(path) => args.config_path = Some(path.into()),
+                        None => bail!("--config needs a path"),
+                    }
+                }
+                "--write-default-config" => {
+                    let path = iter.next_if(|a| !a.starts_with("--")).map(PathBuf::from);
+                    args.write_default_config = Some(path);
+                }
+                "--convert-db" => {
+                    match (iter.next(), iter.next()) {
+                        (Some(from), Some(to)) => args.convert_db = Some((from.into(), to.into())),
+                        _ => bail!("--convert-db needs 2 paths"),
+                    }
+                }
+                _ => {
+                    if let Some(path) = arg.strip_prefix("--config=") {
+                        args.config_path = Some(path.into());
+                    } else {
+                        bail!("unknown argument: {arg}\n{USAGE}");
+                    }
+                }
+            }
+        }
+        Ok(args)
+    }
+}
+
+const USAGE: &str = "usage: musiman [--config <path>] [--write-default-config [path]] [--convert-db <from> <to>]";
 
 pub async fn run() -> Result<()> {
     init_logger().expect("failed to init logger");
 
+    let args = Args::parse()?;
+    if let Some(path) = args.write_default_config {
+        let path = write_default_config(path)?;
+        println!("default config written to {}", path.to_string_lossy());
+        return Ok(());
+    }
+    init_config(args.config_path)?; // before the terminal is setup, so that the errors can be seen
+    if let Some((from, to)) = args.convert_db {
+        storage::convert(&from, &to)?;
+        println!("converted {} to {}", from.to_string_lossy(), to.to_string_lossy());
+        return Ok(());
+    }
+
     // yt_manager::test().unwrap();
     // return Ok(());
 
+    // the db is loaded before the terminal is setup too, so that errors (a newer/broken db) can be returned normally
+    let mut app = App::load()?;
+
     let hook = take_hook();
     set_hook(Box::new(move |info| {
         // create new Terminal if panic
@@ -67,14 +137,14 @@ pub async fn run() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // create app and run it
-    let mut app = App::load().unwrap(); // no throwing error, as it would not call restore_terminal
-    app.run_app(&mut terminal).await.unwrap();
+    let res = app.run_app(&mut terminal).await;
 
-    restore_terminal(&mut terminal)?;
+    // restoring fails if the terminal was closed (SIGHUP), but the db should still be saved
+    let restored = restore_terminal(&mut terminal);
     app.content_manager.save()?;
+    restored?;
 
-    Ok(())
+    res
 }
 
 fn restore_terminal<B>(terminal: &mut Terminal<B>) -> Result<()> 
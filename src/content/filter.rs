@@ -0,0 +1,102 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::{
+    cmp::Reverse,
+    cell::RefCell,
+};
+
+use crate::{
+    content::register::{
+        ContentProviderID,
+        ID,
+    },
+    app::app::SelectedIndex,
+};
+
+/// narrows down the items shown for a provider. the selection still lives in the provider
+/// so everything that acts on the selected item works the same with or without a filter
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub provider: ContentProviderID, // weak
+    query: String,
+    /// index in the filtered list, only used for rendering
+    pub display_index: SelectedIndex,
+    /// the items of the provider and their matches for the current query. the list is shown, and the selection moves through it
+    /// on every key press, so the matching is only redone when the query or the items change
+    cache: RefCell<Option<(Vec<ID>, Vec<usize>)>>,
+}
+
+impl Filter {
+    pub fn new(provider: ContentProviderID) -> Self {
+        Self {
+            provider,
+            query: Default::default(),
+            display_index: Default::default(),
+            cache: Default::default(),
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.cache.take();
+    }
+
+    /// indices of the items that match the query, best match first. text gives the text an item is matched against
+    pub fn matches(&self, items: Vec<ID>, text: impl Fn(ID) -> String) -> Vec<usize> {
+        let mut cache = self.cache.borrow_mut();
+        if let Some((cached_items, matches)) = cache.as_ref() {
+            if *cached_items == items {
+                return matches.clone();
+            }
+        }
+        let matches = self.match_texts(items.iter().map(|&id| text(id)).collect());
+        *cache = Some((items, matches.clone()));
+        matches
+    }
+
+    fn match_texts(&self, texts: Vec<String>) -> Vec<usize> {
+        if self.query.trim().is_empty() {
+            return (0..texts.len()).collect();
+        }
+        let mut scored = texts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, text)| fuzzy_score(&self.query, text).map(|score| (i, score)))
+        .collect::<Vec<_>>();
+        scored.sort_by_key(|&(_, score)| Reverse(score)); // stable, so equal matches stay in order
+        scored.into_iter().map(|(i, _)| i).collect()
+    }
+}
+
+/// every char of the query (ignoring case and spaces) has to appear in the text in the same order.
+/// runs of consecutive chars and chars at the start of words score higher, gaps score lower
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text = text.chars().flat_map(char::to_lowercase).collect::<Vec<_>>();
+    let mut score = 0;
+    let mut start = 0;
+    let mut last_match: Option<usize> = None;
+    for qc in query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()) {
+        let pos = start + text[start..].iter().position(|&c| c == qc)?;
+        score += 1;
+        match last_match {
+            Some(last) if last + 1 == pos => score += 5,
+            Some(last) => score -= (pos - last - 1).min(5) as i64,
+            None => (),
+        }
+        if pos == 0 || !text[pos-1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(pos);
+        start = pos + 1;
+    }
+    Some(score)
+}
@@ -13,6 +13,10 @@ use std::{
     thread,
     fmt::Debug,
     borrow::Cow,
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
 };
 use tokio::{
     sync::mpsc::{
@@ -28,6 +32,8 @@ use crate::{
     content::{
         providers::{
             ContentProvider,
+            file_explorer::FileExplorer,
+            traits::Loadable,
         },
         manager::{
             manager::ContentManager,
@@ -35,6 +41,7 @@ use crate::{
         },
         register::{
             ContentProviderID,
+            SongID,
             GlobalProvider,
             ID,
         },
@@ -49,6 +56,11 @@ use crate::{
             action::PyAction,
             manager::PyManager,
         },
+        notifier::{
+            Notification,
+            Severity,
+        },
+        watcher,
     },
     image::UnprocessedImage,
 };
@@ -119,6 +131,7 @@ pub enum ContentManagerAction {
     ClearImage,
     RefreshDisplayContent,
     PlaySongURI {
+        id: SongID,
         uri: String,
     },
     OpenEditForCurrent,
@@ -136,6 +149,11 @@ pub enum ContentManagerAction {
         instant: std::time::Instant,
         message: Cow<'static, str>,
     },
+    Notify {
+        notif: Notification,
+    },
+    /// see ContentManager::request_save
+    RequestSave,
     None,
 }
 
@@ -147,8 +165,13 @@ impl ContentManagerAction {
             Self::TryLoadContentProvider {loader_id} => {
                 let cp = ch.get_provider_mut(loader_id).as_loadable();
                 if let Some(cp) = cp {
-                    let action = cp.maybe_load(loader_id)?;
-                    action.apply(ch)?;
+                    match cp.maybe_load(loader_id) {
+                        Ok(action) => action.apply(ch)?,
+                        Err(err) => {
+                            let name = ch.get_provider(loader_id).as_display().get_name();
+                            ch.notify_error(format!("failed to load '{name}': {err}"));
+                        }
+                    }
                 }
             }
             Self::LoadContentProvider {songs, content_providers, loader_id} => {
@@ -179,11 +202,13 @@ impl ContentManagerAction {
                 let loaded_id = ch.alloc_content_provider(cp);
                 let loader = ch.get_provider_mut(id);
                 loader.as_provider_mut().unwrap().add_provider(loaded_id);
+                ch.request_save();
             }
             Self::AddCPToCPAndContentStack {id, cp} => {
                 let loaded_id = ch.alloc_content_provider(cp);
                 let loader = ch.get_provider_mut(id);
                 loader.as_provider_mut().unwrap().add_provider(loaded_id);
+                ch.request_save();
 
                 ContentManagerAction::PushToContentStack {id: loaded_id.into()}.apply(ch)?;
                 ContentManagerAction::RefreshDisplayContent.apply(ch)?;
@@ -212,7 +237,25 @@ impl ContentManagerAction {
             Self::PopContentStack => {
                 match ch.content_stack.pop() {
                     Some(id) => {
+                        if ch.filter.as_ref().map(|f| GlobalProvider::from(f.provider) == id).unwrap_or(false) {
+                            ch.filter = None;
+                        }
+                        // explorers are read again when they are opened again, so their dirs need not be watched till then
+                        let unloaded = match id {
+                            GlobalProvider::ContentProvider(id) => {
+                                ch.get_provider_mut(id)
+                                .as_any_mut()
+                                .downcast_mut::<FileExplorer>()
+                                .filter(|fe| fe.is_loaded())
+                                .map(|fe| (fe.path.clone().into_owned(), fe.unload()))
+                            }
+                            GlobalProvider::Notifier => None,
+                        };
                         ch.unregister(id);
+                        if let Some((dir, ids)) = unloaded {
+                            ids.into_iter().for_each(|id| ch.unregister(id));
+                            watcher::unwatch_unused(ch, vec![dir])?;
+                        }
                     }
                     None => (),
                 }
@@ -236,8 +279,8 @@ impl ContentManagerAction {
                 ch.image_handler.clear_image();
                 ch.app_action_sender.send(AppAction::Redraw)?;
             }
-            Self::PlaySongURI {uri} => {
-                ch.player.play(uri)?;
+            Self::PlaySongURI {id, uri} => {
+                ch.play_uri(id, uri)?;
             }
             Self::OpenEditForCurrent => {
                 ch.open_edit_for_current()?;
@@ -258,6 +301,13 @@ impl ContentManagerAction {
                 let duration = std::time::Instant::now().duration_since(instant).as_secs_f64();
                 debug!("{message}: {duration}");
             }
+            Self::Notify { notif } => {
+                ch.notifier.notify(notif);
+                ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+            }
+            Self::RequestSave => {
+                ch.request_save();
+            }
         }
         Ok(())
     }
@@ -291,6 +341,11 @@ impl Default for ParallelHandle {
 }
 
 impl ParallelHandle {
+    /// for sending actions from places that are not ParallelActions (eg. the file watcher)
+    pub fn sender(&self) -> UnboundedSender<ContentManagerAction> {
+        self.sender.clone()
+    }
+
     fn run(&mut self, action: ParallelAction) { // TODO: use threadpool crate instead of creating threads as they are required
         let action = match action {
             ParallelAction::Python(a) => {
@@ -301,7 +356,7 @@ impl ParallelHandle {
         let sender = self.sender.clone();
         match self.handles.iter_mut().filter(|h| h.is_finished()).next() {
             Some(h) => {
-                let handle = thread::spawn(move || action.run(sender));
+                let handle = Self::spawn(action, sender);
                 match std::mem::replace(h, handle).join() {
                     Ok(Ok(())) => (),
                     Err(e) => log::error!("{:#?}", e),
@@ -309,11 +364,31 @@ impl ParallelHandle {
                 }
             }
             None => {
-                self.handles.push(thread::spawn(move || action.run(sender)));
+                self.handles.push(Self::spawn(action, sender));
             }
         }
     }
 
+    /// errors and panics are sent back as notifications
+    fn spawn(action: RustParallelAction, sender: UnboundedSender<ContentManagerAction>) -> thread::JoinHandle<Result<()>> {
+        thread::spawn(move || {
+            let s = sender.clone();
+            let message = match panic::catch_unwind(AssertUnwindSafe(move || action.run(s))) {
+                Ok(Ok(())) => return Ok(()),
+                Ok(Err(err)) => format!("{err}"),
+                Err(e) => {
+                    let msg = e.downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or(e.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                    format!("parallel action panicked: {msg}")
+                }
+            };
+            sender.send(ContentManagerAction::Notify { notif: Notification::new(Severity::Error, message) })?;
+            Ok(())
+        })
+    }
+
     pub async fn recv(&mut self) -> ContentManagerAction {
         let a1 = self.receiver.recv();
         let a2 = self.yt_man.recv();
@@ -8,8 +8,17 @@ use crate::{
 };
 
 
+use std::{
+    thread::JoinHandle,
+    time::{
+        Duration,
+        Instant,
+    },
+};
 use musiplayer::Player;
 use anyhow::Result;
+use tui::text::Span;
+use serde::{Serialize, Deserialize};
 use tokio::sync::mpsc::{
     unbounded_channel,
     UnboundedReceiver,
@@ -23,7 +32,12 @@ use crate::{
             main_provider::MainProvider,
             queue_provider::QueueProvider,
             queue::Queue,
-            traits::CPProvider,
+            downloads::Downloads,
+            file_explorer::FileExplorer,
+            traits::{
+                CPProvider,
+                Loadable,
+            },
         },
         register::{
             ContentRegister,
@@ -35,15 +49,19 @@ use crate::{
         },
         song::Song,
         stack::ContentStack,
-        manager::action::{
-            ParallelHandle,
-            ContentManagerAction,
+        manager::{
+            action::{
+                ParallelHandle,
+                ContentManagerAction,
+            },
+            callback::ContentManagerCallbackTrait,
         },
         stack::ContentState,
         display::{
             DisplayContext,
             DisplayState,
         },
+        filter::Filter,
     },
     app::{
         action::AppAction,
@@ -51,13 +69,25 @@ use crate::{
         display::ListBuilder,
     },
     service::{
-        db::handler::DBHandler,
+        db::{
+            handler::DBHandler,
+            migrations::DB_VERSION,
+        },
         editors::{
             Yanker,
             EditManager,
             Edit,
         },
-        notifier::Notifier,
+        notifier::{
+            Notifier,
+            Notification,
+            Severity,
+        },
+        watcher::{
+            self,
+            FileWatcher,
+        },
+        config::config,
     },
     image::ImageHandler,
 };
@@ -70,10 +100,26 @@ pub struct ContentManager {
     pub edit_manager: EditManager,
     pub image_handler: ImageHandler,
     pub player: Player, // FIX: memory leak somewhere maybe. (the ram usage keeps increasing) // https://github.com/sdroege/gstreamer-rs/blob/main/examples/src/bin/play.rs
-    notifier: Notifier,
+    pub notifier: Notifier,
+    pub filter: Option<Filter>,
+    pub watcher: FileWatcher,
     
-    active_queue: Option<ContentProviderID>, // can also be a bunch of queues? like -> play all artists
+    pub active_queue: Option<ContentProviderID>, // can also be a bunch of queues? like -> play all artists
     pub active_song: Option<SongID>,
+    /// the Downloads of this session (if anything was downloaded yet)
+    pub downloads: Option<ContentProviderID>,
+    pub volume: Volume,
+    /// where to continue the active song from, once it starts playing
+    resume: Option<Resume>,
+    /// an edit that should not be lost happened since the last save
+    save_requested: bool,
+    last_save: Instant,
+    /// the autosave that is being written in the background (if any)
+    saving: Option<JoinHandle<()>>,
+    /// the song that is expected to play next, and its uri once it arrives
+    prefetched: Option<Prefetch>,
+    /// the song whose uri is being fetched. the player stays finished till then
+    loading_song: Option<(SongID, Instant)>,
 
     pub parallel_handle: ParallelHandle,
 
@@ -129,31 +175,53 @@ impl ContentManager {
         dbg!("unregister id", id);
         match id {
             GlobalContent::ID(id) => {
-                match id {
-                    ID::Song(id) => {
-                        let _ = self.songs.unregister(id);
+                let mut dropped_dirs = vec![];
+                unregister_from(&mut self.songs, &mut self.content_providers, id, &mut dropped_dirs);
+                if !dropped_dirs.is_empty() {
+                    if let Err(err) = watcher::unwatch_unused(self, dropped_dirs) {
+                        error!("could not unwatch dirs: {err}");
                     }
-                    ID::ContentProvider(id) => {
-                        let cp = self.content_providers.unregister(id);
-                        match cp {
-                            Some(cp) => {
-                                if let Some(cp) = cp.as_song_provider() {
-                                    for &s_id in cp.songs() {
-                                        let _ = self.unregister(s_id);
-                                    }
-                                }
-                                if let Some(cp) = cp.as_provider() {
-                                    for &cp_id in cp.providers() {
-                                        let _ = self.unregister(cp_id);
-                                    }
-                                }
-                            }
-                            None => (),
+                }
+            }
+            GlobalContent::Notifier => (),
+        }
+    }
+}
+
+/// unregisters the id. if it gets deallocated, everything it holds is unregistered too.
+/// the dirs of the loaded FileExplorers that got deallocated are collected in dropped_dirs
+fn unregister_from(
+    songs: &mut ContentRegister<Song, SongID>,
+    content_providers: &mut ContentRegister<ContentProvider, ContentProviderID>,
+    id: ID,
+    dropped_dirs: &mut Vec<String>,
+) {
+    match id {
+        ID::Song(id) => {
+            let _ = songs.unregister(id);
+        }
+        ID::ContentProvider(id) => {
+            let cp = content_providers.unregister(id);
+            match cp {
+                Some(cp) => {
+                    if let Some(fe) = cp.as_any().downcast_ref::<FileExplorer>() {
+                        if fe.is_loaded() {
+                            dropped_dirs.push(fe.path.clone().into_owned());
+                        }
+                    }
+                    if let Some(cp) = cp.as_song_provider() {
+                        for &s_id in cp.songs() {
+                            unregister_from(songs, content_providers, s_id.into(), dropped_dirs);
+                        }
+                    }
+                    if let Some(cp) = cp.as_provider() {
+                        for &cp_id in cp.providers() {
+                            unregister_from(songs, content_providers, cp_id.into(), dropped_dirs);
                         }
                     }
                 }
+                None => (),
             }
-            GlobalContent::Notifier => (),
         }
     }
 }
@@ -167,14 +235,24 @@ impl ContentManager {
                 let id = self.content_stack.last();
                 match id {
                     GlobalProvider::ContentProvider(id) => {
-                        self.display_provider(id, DisplayState::Normal)
+                        let mut lb = self.display_provider(id, DisplayState::Normal);
+                        if let Some(matches) = self.filter_matches(id) {
+                            let mut items = lb.items.into_iter().map(Some).collect::<Vec<_>>();
+                            lb.items = matches
+                            .into_iter()
+                            .filter_map(|i| items.get_mut(i).map(Option::take).flatten())
+                            .collect();
+                            let query = self.filter.as_ref().map(|f| f.query().to_owned()).unwrap_or_default();
+                            lb.extend_title(Span::raw(format!(" /{query}")));
+                        }
+                        lb
                     }
-                    GlobalProvider::Notifier => todo!(),
+                    GlobalProvider::Notifier => self.notifier.display(),
                 }
             }
             ContentState::Menu { ctx, id } => {
                 match *id {
-                    GlobalContent::Notifier => todo!(),
+                    GlobalContent::Notifier => self.notifier.display_menu(ctx),
                     GlobalContent::ID(id) => {
                         match id {
                             ID::Song(id) => {
@@ -189,7 +267,7 @@ impl ContentManager {
             }
             ContentState::Edit { ctx, id } => {
                 match *id {
-                    GlobalContent::Notifier => todo!(),
+                    GlobalContent::Notifier => unreachable!(), // notifier is not editable
                     GlobalContent::ID(id) => {
                         match id {
                             ID::Song(id) => {
@@ -216,7 +294,12 @@ impl ContentManager {
         })
     }
     fn display_song(&self, id: SongID, state: DisplayState) -> ListBuilder<'static> {
-        todo!()
+        let song = self.get_song(id);
+        match state {
+            DisplayState::Edit(ctx) => song.as_editable().unwrap().display_editables(ctx),
+            DisplayState::Menu(_) => todo!(),
+            DisplayState::Normal => unreachable!(),
+        }
     }
 }
 
@@ -231,6 +314,8 @@ impl ContentManager {
             cp.alloc(main_provider.into())
         };
         let (sender, receiver) = unbounded_channel();
+        let parallel_handle = ParallelHandle::default();
+        let watcher = FileWatcher::new(parallel_handle.sender());
         let ch = Self {
             songs: ContentRegister::new(),
             content_providers: cr,
@@ -239,9 +324,19 @@ impl ContentManager {
             image_handler: Default::default(),
             player: Player::new()?,
             notifier: Notifier::new(),
+            filter: None,
+            watcher,
             active_queue: None,
             active_song: None,
-            parallel_handle: Default::default(),
+            downloads: None,
+            volume: Default::default(),
+            resume: None,
+            save_requested: false,
+            last_save: Instant::now(),
+            saving: None,
+            prefetched: None,
+            loading_song: None,
+            parallel_handle,
             app_action_sender: sender,
             app_action_receiver: receiver,
         };
@@ -266,14 +361,26 @@ impl ContentManager {
                 *db.content_providers
                 .get_mut(db.main_provider)
                 .unwrap() = mp.into();
-                Some(Self {
+                let mut ch = Self {
                     songs: db.songs,
                     content_providers: db.content_providers,
                     content_stack: ContentStack::new(db.main_provider),
                     edit_manager: db.edit_manager,
+                    volume: db.volume,
 
                     ..Self::new()?
-                })
+                };
+                ch.apply_volume()?;
+                if let Some(v) = db.migrated_from {
+                    ch.notify(Severity::Info, format!("the db was upgraded from version {v} to {DB_VERSION}"));
+                }
+                if let Some(path) = db.recovered_from {
+                    ch.notify(Severity::Warning, format!("the db could not be read. loaded the backup {}", path.to_string_lossy()));
+                }
+                if let Some(session) = db.session {
+                    ch.restore_session(session)?;
+                }
+                Some(ch)
             }
             None => {
                 None
@@ -283,37 +390,153 @@ impl ContentManager {
     }
 
     pub fn save(mut self) -> Result<()> {
-        (0..self.content_stack.len()-1)
-        .filter_map(|_| self.content_stack.pop())
-        .collect::<Vec<_>>()
-        .into_iter()
-        .for_each(|id| self.unregister(id));
+        self.save_now()
+    }
 
-        self.active_song.map(|id| self.unregister(id));
-        self.active_queue.map(|id| self.unregister(id));
+    /// waits for the autosave that is being written (if any), so that an older snapshot never replaces this one
+    pub fn save_now(&mut self) -> Result<()> {
+        if let Some(saving) = self.saving.take() {
+            let _ = saving.join();
+        }
+        self.snapshot().save()
+    }
 
-        self.get_main_provider()
-        .providers()
-        .cloned()
-        .collect::<Vec<_>>()
-        .into_iter()
-        .for_each(|id| self.unregister(id));
-        self.get_main_provider_mut().providers_mut().clear();
-        
+    /// for edits that should not be lost (pastes, cuts, new providers). the save happens in maybe_autosave
+    pub fn request_save(&mut self) {
+        self.save_requested = true;
+    }
+
+    /// saves every config().autosave_interval seconds, and soon after a save is requested.
+    /// the db is encoded and written in another thread, so only the snapshot is taken on the ui loop.
+    /// nothing is saved when the app panics, so the edits since the last autosave are lost then
+    pub fn maybe_autosave(&mut self) -> Result<()> {
+        let interval = config().autosave_interval;
+        if interval == 0 {
+            return Ok(());
+        }
+        if self.saving.as_ref().map(|s| !s.is_finished()).unwrap_or(false) {
+            return Ok(()); // tried again on the next update
+        }
+        let since = self.last_save.elapsed();
+        let due = since >= Duration::from_secs(interval);
+        let requested = self.save_requested && since >= MIN_SAVE_GAP;
+        if !due && !requested {
+            return Ok(());
+        }
+        self.save_requested = false;
+        self.last_save = Instant::now();
+        let db = self.snapshot();
+        let sender = self.parallel_handle.sender();
+        self.saving = Some(std::thread::spawn(move || {
+            if let Err(err) = db.save() {
+                let notif = Notification::new(Severity::Error, format!("autosave failed: {err}"));
+                let _ = sender.send(ContentManagerAction::Notify { notif });
+            }
+        }));
+        Ok(())
+    }
+
+    /// a copy of the db as it gets saved. the content stack and the active song/queue are saved as the Session (which keeps
+    /// their registers). the downloads and the non persistent providers in MainProvider are dropped from the copy
+    pub fn snapshot(&self) -> DBHandler {
+        let mut songs = self.songs.clone();
+        let mut content_providers = self.content_providers.clone();
         let mp = self.content_stack.main_provider();
-        let songs = self.songs;
-        let cps = self.content_providers;
+
+        let (stack, dropped): (Vec<_>, Vec<_>) = (1..self.content_stack.len())
+        .filter_map(|i| match self.content_stack.get(i) {
+            GlobalProvider::ContentProvider(id) => Some(id),
+            GlobalProvider::Notifier => None,
+        })
+        .partition(|&id| Some(id) != self.downloads);
+        let session = Session {
+            main_selected: self.get_provider(mp).get_selected_index().selected_index(),
+            stack: stack
+            .into_iter()
+            .map(|id| (id, self.get_provider(id).get_selected_index().selected_index()))
+            .collect(),
+            active_queue: self.active_queue,
+            active_song: self.active_song,
+            position: self.active_song.and_then(|_| self.player.position().ok()).unwrap_or(0.0),
+        };
+
+        let session_ids = dropped
+        .into_iter()
+        .map(Into::into)
+        .chain(self.downloads.map(Into::into))
+        .chain(self.get_main_provider().providers().cloned().map(Into::into))
+        .collect::<Vec<ID>>();
+        session_ids
+        .into_iter()
+        .for_each(|id| unregister_from(&mut songs, &mut content_providers, id, &mut vec![])); // a copy of the registers, nothing to unwatch
+        content_providers
+        .get_mut(mp)
+        .unwrap()
+        .as_provider_mut()
+        .unwrap()
+        .providers_mut()
+        .clear();
+
         // self.edit_manager.yanker.take();
-        let edit_manager = self.edit_manager;
         DBHandler {
+            version: DB_VERSION,
             main_provider: mp,
             songs,
-            content_providers: cps,
-            edit_manager,
-        }.save()?;
+            content_providers,
+            edit_manager: self.edit_manager.clone(),
+            volume: self.volume,
+            session: Some(session),
+            recovered_from: None,
+            migrated_from: None,
+        }
+    }
+
+    /// the session holds a register for everything in it, so they are just moved over to the content stack/active queue/active song.
+    /// the song itself starts playing in resume_playback
+    fn restore_session(&mut self, session: Session) -> Result<()> {
+        let Session { main_selected, stack, active_queue, active_song, position } = session;
+        let mp = self.content_stack.main_provider();
+        let mut entries = vec![(mp, main_selected)];
+        for (id, selected) in stack {
+            self.content_stack.push(id);
+            ContentManagerAction::TryLoadContentProvider { loader_id: id }.apply(self)?;
+            entries.push((id, selected));
+        }
+        for (id, selected) in entries {
+            let cp = self.get_provider_mut(id);
+            let len = cp.get_size();
+            cp.get_selected_index_mut().select(selected.min(len.saturating_sub(1)));
+        }
+        self.active_queue = active_queue;
+        self.active_song = active_song;
+        if active_song.is_some() {
+            self.resume = Some(Resume {
+                position,
+                paused: config().resume_paused,
+            });
+        }
         Ok(())
     }
 
+    /// plays the active song of the restored session. the position is restored in update_playback once the song starts
+    pub fn resume_playback(&mut self) -> Result<()> {
+        match (self.resume.is_some(), self.active_song) {
+            (true, Some(id)) => self.play_song(id),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get_or_add_downloads(&mut self) -> ContentProviderID {
+        match self.downloads {
+            Some(id) => id,
+            None => {
+                let id = self.alloc_content_provider(Downloads::new().into()); // for being stored in ContentManager.downloads
+                self.downloads = Some(id);
+                id
+            }
+        }
+    }
+
     pub fn get_provider(&self, id: ContentProviderID) -> &ContentProvider {
         self.content_providers.get(id).unwrap()
     }
@@ -356,7 +579,7 @@ impl ContentManager {
         let len = self.content_stack.len();
         (0..len).map(|i| self.content_stack.get(i)) // content_stack.state ids do not count in register
         .map(|id| match id {
-            GlobalProvider::Notifier => todo!(),
+            GlobalProvider::Notifier => vec![].into_iter(),
             GlobalProvider::ContentProvider(id) => cp_ids(self, id, &mut unique_provider_ids).into_iter(),
         })
         .flatten()
@@ -373,14 +596,19 @@ impl ContentManager {
         .edit_stack
         .iter()
         .chain(self.edit_manager.undo_stack.iter())
-        .map(|e| match e {
-            Edit::Pasted { yank, yanked_to, .. } => {
-                yank.iter().chain([*yanked_to].into_iter().map(Into::into))
-            }
-            Edit::Yanked { yank, yanked_from, .. } => {
-                yank.iter().chain([*yanked_from].into_iter().map(Into::into))
+        .map(|e| -> Vec<ID> {
+            match e {
+                Edit::Pasted { yank, yanked_to, .. } => {
+                    yank.iter().chain([*yanked_to].into_iter().map(Into::into)).collect()
+                }
+                Edit::Yanked { yank, yanked_from, .. } => {
+                    yank.iter().chain([*yanked_from].into_iter().map(Into::into)).collect()
+                }
+                Edit::TagEdit { changes } => {
+                    changes.iter().map(|c| c.song.into()).collect()
+                }
+                Edit::TextEdit { .. } => todo!(),
             }
-            Edit::TextEdit { .. } => todo!(),
         })
         .flatten()
         .for_each(|id| match id {
@@ -405,7 +633,22 @@ impl ContentManager {
 
         self.active_queue.map(|id| provider_keys.push(id));
         self.active_song.map(|id| song_keys.push(id));
+        self.downloads
+        .map(|id| cp_ids(self, id, &mut unique_provider_ids))
+        .unwrap_or_default()
+        .into_iter()
+        .for_each(|id| match id {
+            ID::Song(id) => {
+                song_keys.push(id);
+            }
+            ID::ContentProvider(id) => {
+                provider_keys.push(id);
+            }
+        });
         provider_keys.push(self.get_main_provider().queue_provider);
+        self.get_main_provider().artist_provider.map(|id| provider_keys.push(id));
+        self.get_main_provider().playlist_provider.map(|id| provider_keys.push(id));
+        self.get_main_provider().library.map(|id| provider_keys.push(id));
 
         // self.songs;
         // self.content_providers;
@@ -413,7 +656,11 @@ impl ContentManager {
         // self.edit_manager;
         // self.active_queue;
         // self.active_song;
+        // self.downloads
         // self.main_provider().queue_provider
+        // self.main_provider().artist_provider
+        // self.main_provider().playlist_provider
+        // self.main_provider().library
 
         let key_frequencies = song_keys
         .into_iter()
@@ -494,9 +741,6 @@ impl ContentManager {
                 let b = serde_yaml::from_str::<ContentRegister<ContentProvider, ContentProviderID>>(&a);
                 dbg!(b);
             }
-            't' => {
-                dbg!(crate::service::db::musimanager_db::test());
-            }
             _ => {}
         }
     }
@@ -543,6 +787,9 @@ impl ContentManager {
                 let id = self.content_stack.last();
                 match id {
                     GlobalProvider::ContentProvider(id) => {
+                        if self.selection_filtered_out(id) {
+                            return Ok(());
+                        }
                         let cp = self.get_provider_mut(id);
                         let content_id = cp.get_selected();
                         match content_id {
@@ -575,7 +822,8 @@ impl ContentManager {
                         }
                     }
                     GlobalContent::Notifier => {
-                        todo!()
+                        let action = self.notifier.apply_option(ctx);
+                        action.apply(self)?;
                     }
                 }
             }
@@ -589,13 +837,13 @@ impl ContentManager {
                                 action.apply(self)?;
                             }
                             ID::Song(id) => {
-                                todo!()
+                                let song = self.songs.get_mut(id).unwrap();
+                                let action = song.as_editable_mut().unwrap().select_editable(ctx, id);
+                                action.apply(self)?;
                             }
                         }
                     }
-                    GlobalContent::Notifier => {
-                        todo!()
-                    }
+                    GlobalContent::Notifier => unreachable!(), // notifier is not editable
                 }
             }
             ContentState::GlobalMenu(i) => {
@@ -659,7 +907,7 @@ impl ContentManager {
                 }
             }
             GlobalContent::Notifier => {
-                todo!()
+                self.content_stack.open_menu(GlobalContent::Notifier);
             }
         }
         ContentManagerAction::RefreshDisplayContent.apply(self)?;
@@ -686,6 +934,9 @@ impl ContentManager {
                 let id = self.content_stack.last();
                 match id {
                     GlobalProvider::ContentProvider(id) => {
+                        if self.selection_filtered_out(id) {
+                            return Ok(());
+                        }
                         let cp = self.get_provider(id);
                         let id = cp.get_selected();
                         self.open_edit_for(id)?;
@@ -707,7 +958,9 @@ impl ContentManager {
             GlobalContent::ID(id) => {
                 match id {
                     ID::Song(id) => {
-                        todo!()
+                        if self.get_song(id).as_editable().is_some() {
+                            self.content_stack.open_edit(id);
+                        }
                     }
                     ID::ContentProvider(id) => {
                         let cp = self.get_provider(id);
@@ -718,7 +971,7 @@ impl ContentManager {
                 }
             }
             GlobalContent::Notifier => {
-                todo!()
+                // do nothing
             }
         }
         ContentManagerAction::RefreshDisplayContent.apply(self)?;
@@ -736,6 +989,9 @@ impl ContentManager {
                 match id {
                     GlobalProvider::Notifier => (),
                     GlobalProvider::ContentProvider(id) => {
+                        if self.selection_filtered_out(id) {
+                            return Ok(());
+                        }
                         let cp = self.get_provider(id);
                         let selected_id = cp.get_selected();
                         let index = cp.get_selected_index().selected_index();
@@ -755,6 +1011,118 @@ impl ContentManager {
     }
 }
 
+// methods related to filtering the items of the current provider
+impl ContentManager {
+    /// starts filtering the current provider. returns the query if it was already being filtered
+    pub fn start_filter(&mut self) -> Option<String> {
+        if !matches!(self.content_stack.get_state(), ContentState::Normal) {
+            return None;
+        }
+        let id = match self.content_stack.last() {
+            GlobalProvider::ContentProvider(id) => id,
+            GlobalProvider::Notifier => return None,
+        };
+        match &self.filter {
+            Some(f) if f.provider == id => (),
+            _ => self.filter = Some(Filter::new(id)),
+        }
+        self.filter.as_ref().map(|f| f.query().to_owned())
+    }
+
+    /// the best match gets selected
+    pub fn set_filter_query(&mut self, query: String) -> Result<()> {
+        let id = match self.filter.as_mut() {
+            Some(f) => {
+                f.set_query(query);
+                f.provider
+            }
+            None => return Ok(()),
+        };
+        let best = self.filter_matches(id)
+        .map(|m| m.first().cloned())
+        .flatten();
+        if let Some(i) = best {
+            self.get_provider_mut(id).get_selected_index_mut().select(i);
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(self)?;
+        Ok(())
+    }
+
+    /// returns false if there was no filter
+    pub fn clear_filter(&mut self) -> Result<bool> {
+        if self.filter.take().is_none() {
+            return Ok(false);
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(self)?;
+        Ok(true)
+    }
+
+    /// the index of the selected item in the list that is shown
+    pub fn get_display_index(&mut self) -> &mut SelectedIndex {
+        let normal = matches!(self.content_stack.get_state(), ContentState::Normal);
+        let filtered = match self.content_stack.last() {
+            GlobalProvider::ContentProvider(id) if normal => {
+                self.filter_matches(id).map(|matches| (id, matches))
+            }
+            _ => None,
+        };
+        let (id, matches) = match filtered {
+            Some(f) => f,
+            None => return self.get_selected_index(),
+        };
+        let selected = self.get_provider(id).get_selected_index().selected_index();
+        let pos = matches.iter().position(|&i| i == selected).unwrap_or(0);
+        let filter = self.filter.as_mut().unwrap();
+        filter.display_index.select(pos);
+        &mut filter.display_index
+    }
+
+    /// indices of the items of the provider that pass the filter, best match first.
+    /// None if the provider is not being filtered
+    fn filter_matches(&self, id: ContentProviderID) -> Option<Vec<usize>> {
+        let filter = self.filter.as_ref().filter(|f| f.provider == id)?;
+        let items = self.get_provider(id).ids().collect();
+        let matches = filter.matches(items, |id| match id {
+            ID::Song(id) => {
+                let song = self.get_song(id).as_display();
+                [Some(song.title()), song.artist(), song.album()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ")
+            }
+            ID::ContentProvider(id) => {
+                self.get_provider(id).as_display().get_name().into_owned()
+            }
+        });
+        Some(matches)
+    }
+
+    /// if the selected item is hidden by the filter
+    pub fn selection_filtered_out(&self, id: ContentProviderID) -> bool {
+        let selected = self.get_provider(id).get_selected_index().selected_index();
+        self.filter_matches(id)
+        .map(|m| !m.contains(&selected))
+        .unwrap_or(false)
+    }
+
+    fn move_filtered_selection(&mut self, id: ContentProviderID, matches: Vec<usize>, forward: bool) -> bool {
+        let selected = self.get_provider(id).get_selected_index().selected_index();
+        let next = match matches.iter().position(|&i| i == selected) {
+            Some(pos) if forward => matches.get(pos+1).cloned(),
+            Some(pos) => pos.checked_sub(1).map(|p| matches[p]),
+            None => matches.first().cloned(),
+        };
+        match next {
+            Some(i) => {
+                self.get_provider_mut(id).get_selected_index_mut().select(i);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 // methods related song to playback
 impl ContentManager {
     pub fn set_queue(&mut self, id: ContentProviderID, song_id: SongID) {
@@ -821,18 +1189,42 @@ impl ContentManager {
     }
 
     pub fn play_song(&mut self, id: SongID) -> Result<()> {
+        if self.active_song != Some(id) {
+            self.resume = None;
+        }
         self.register(id);
         self.active_song.map(|id| self.unregister(id));
         self.active_song = Some(id);
+        self.loading_song = Some((id, Instant::now()));
 
         self.player.stop().unwrap();
+        let prefetched = self.prefetched.take()
+        .filter(|p| p.song_id == id)
+        .map(|p| p.uri)
+        .flatten();
         let song = self.get_song(id);
-        let play_action = song.play()?;
+        let play_action = match prefetched {
+            Some(uri) => ContentManagerAction::PlaySongURI { id, uri },
+            None => song.play(id)?,
+        };
         let art_action = song.show_art()?;
         play_action.apply(self)?;
         art_action.apply(self)?;
         Ok(())
     }
+    /// the uri might show up after the song timed out or another song was played, so it is dropped unless id is still loading
+    pub fn play_uri(&mut self, id: SongID, uri: String) -> Result<()> {
+        if self.loading_song.map(|(loading, _)| loading) != Some(id) {
+            return Ok(());
+        }
+        self.loading_song = None;
+        let played = self.player.play(uri);
+        if played.is_err() {
+            self.resume = None;
+        }
+        played?;
+        self.apply_volume() // the player might not keep it between songs
+    }
     pub fn toggle_song_pause(&mut self) {
         self.player.toggle_pause().unwrap();
     }
@@ -851,6 +1243,92 @@ impl ContentManager {
         }
         Ok(())
     }
+    /// called when the player reaches the end of the current song
+    pub fn song_finished(&mut self) -> Result<()> {
+        let id = match self.active_queue {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        let q = self.get_provider_mut(id)
+        .as_any_mut()
+        .downcast_mut::<Queue>()
+        .unwrap();
+        if let Some(id) = q.finished_song() {
+            if let Err(err) = self.play_song(id) {
+                self.notify_error(format!("could not play the next song: {err}"));
+            }
+            ContentManagerAction::RefreshDisplayContent.apply(self)?;
+        }
+        Ok(())
+    }
+
+    /// advances the active queue when the song ends. songs that do not start playing in time are skipped.
+    /// the uri of the next online song is fetched a bit before the current one ends
+    pub fn update_playback(&mut self) -> Result<()> {
+        if let Some((id, instant)) = self.loading_song {
+            if instant.elapsed() < SONG_LOAD_TIMEOUT {
+                return Ok(());
+            }
+            self.loading_song = None;
+            self.resume = None;
+            let title = self.get_song(id).as_display().title().to_owned();
+            self.notify_error(format!("could not load song '{title}'. skipping it"));
+            if let Err(err) = self.next_song() {
+                self.notify_error(format!("could not play the next song: {err}"));
+            }
+            return Ok(());
+        }
+        if let Some(resume) = self.resume {
+            if self.player.duration().map(|d| d > 0.0).unwrap_or(false) {
+                self.resume = None;
+                self.seek_song_to(resume.position)?;
+                if resume.paused {
+                    self.toggle_song_pause();
+                }
+            }
+        }
+        if self.player.is_finished()? {
+            self.song_finished()?;
+        } else if self.player.progress()? > PREFETCH_PROGRESS {
+            self.prefetch_next_song()?;
+        }
+        Ok(())
+    }
+
+    fn prefetch_next_song(&mut self) -> Result<()> {
+        let id = match self.active_queue {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        let next = self.get_provider_mut(id)
+        .as_any_mut()
+        .downcast_mut::<Queue>()
+        .unwrap()
+        .peek_next();
+        let next = match next {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        if self.prefetched.as_ref().map(|p| p.song_id == next).unwrap_or(false) {
+            return Ok(());
+        }
+        let song = self.get_song(next);
+        if !song.is_online() {
+            return Ok(());
+        }
+        let action = song.get_uri(Box::new(move |uri: String| {
+            Ok(ContentManagerAction::Callback {
+                callback: PrefetchedUri { song_id: next, uri }.into(),
+            })
+        }));
+        self.prefetched = Some(Prefetch { song_id: next, uri: None });
+        match action {
+            Ok(action) => action.apply(self)?,
+            Err(err) => self.notify_error(format!("could not prefetch the next song: {err}")),
+        }
+        Ok(())
+    }
+
     pub fn prev_song(&mut self) -> Result<()> {
         let id = match self.active_queue {
             Some(id) => id,
@@ -869,6 +1347,102 @@ impl ContentManager {
     pub fn seek_song(&mut self, t: f64) -> Result<()> {
         self.player.seek(t)
     }
+    pub fn seek_song_to(&mut self, t: f64) -> Result<()> {
+        let pos = self.player.position()?;
+        self.seek_song(t - pos)
+    }
+
+    /// level is in percent, and is clamped to 0..=100. unmutes the player
+    pub fn set_volume(&mut self, level: i32) -> Result<()> {
+        self.volume.level = level.clamp(0, 100) as u8;
+        self.volume.muted = false;
+        self.apply_volume()
+    }
+    pub fn change_volume(&mut self, by: i32) -> Result<()> {
+        self.set_volume(self.volume.level as i32 + by)
+    }
+    pub fn toggle_mute(&mut self) -> Result<()> {
+        self.volume.muted = !self.volume.muted;
+        self.apply_volume()
+    }
+    fn apply_volume(&mut self) -> Result<()> {
+        let level = if self.volume.muted { 0 } else { self.volume.level };
+        self.player.set_volume(level as f64 / 100.0)
+    }
+}
+
+/// what was open when the app was closed. everything in here holds a register, just like in the ContentManager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub main_selected: usize,
+    /// the providers on the content stack after the main provider, with their selected index
+    pub stack: Vec<(ContentProviderID, usize)>,
+    pub active_queue: Option<ContentProviderID>,
+    pub active_song: Option<SongID>,
+    /// seconds into the active song
+    pub position: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Resume {
+    position: f64,
+    paused: bool,
+}
+
+/// saved in the db, so it stays the same across restarts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Volume {
+    /// in percent
+    pub level: u8,
+    pub muted: bool,
+}
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            level: 100,
+            muted: false,
+        }
+    }
+}
+
+const SONG_LOAD_TIMEOUT: Duration = Duration::from_secs(20);
+/// requested saves wait for this long after the last save, so that a bunch of edits only save once
+const MIN_SAVE_GAP: Duration = Duration::from_secs(5);
+/// fraction of the song after which the next song is prefetched
+const PREFETCH_PROGRESS: f64 = 0.8;
+
+#[derive(Debug)]
+struct Prefetch {
+    song_id: SongID, // weak
+    uri: Option<String>,
+}
+
+#[derive(Debug)]
+struct PrefetchedUri {
+    song_id: SongID,
+    uri: String,
+}
+impl ContentManagerCallbackTrait for PrefetchedUri {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        match ch.prefetched.as_mut() {
+            Some(p) if p.song_id == self.song_id => {
+                p.uri = Some(self.uri);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+// methods related to notifications
+impl ContentManager {
+    pub fn notify<T: Into<String>>(&mut self, severity: Severity, message: T) {
+        self.notifier.notify(Notification::new(severity, message));
+    }
+
+    pub fn notify_error<T: Into<String>>(&mut self, err: T) {
+        self.notify(Severity::Error, err);
+    }
 }
 
 // methods related to managing selectioins
@@ -884,7 +1458,7 @@ impl ContentManager {
                         cp.get_selected_index_mut()
                     }
                     GlobalProvider::Notifier => {
-                        todo!()
+                        self.notifier.get_selected_index_mut()
                     }
                 }
             }
@@ -912,7 +1486,8 @@ impl ContentManager {
                     GlobalContent::ID(id) => {
                         match id {
                             ID::Song(id) => {
-                                todo!()
+                                let song = self.songs.get(id).unwrap();
+                                song.as_editable().unwrap().num_editables(ctx)
                             }
                             ID::ContentProvider(id) => {
                                 let cp = self.content_providers.get_mut(id).unwrap();
@@ -920,7 +1495,7 @@ impl ContentManager {
                             }
                         }
                     }
-                    GlobalContent::Notifier => todo!(),
+                    GlobalContent::Notifier => unreachable!(), // notifier is not editable
                 };
                 let i = ctx.last_mut();
                 if i.selected_index()+1 < num_items {
@@ -941,7 +1516,7 @@ impl ContentManager {
                             }
                         }
                     }
-                    GlobalContent::Notifier => todo!(),
+                    GlobalContent::Notifier => self.notifier.num_options(ctx),
                 };
                 let i = ctx.last_mut();
                 if i.selected_index()+1 < num_items {
@@ -984,19 +1559,25 @@ impl ContentManager {
     fn increment_selection_on<T: Into<GlobalProvider>>(&mut self, id: T) -> bool {
         match id.into() {
             GlobalProvider::ContentProvider(id) => {
+                if let Some(matches) = self.filter_matches(id) {
+                    return self.move_filtered_selection(id, matches, true);
+                }
                 let cp = self.get_provider_mut(id);
                 cp.selection_increment()
             }
-            GlobalProvider::Notifier => todo!(),
+            GlobalProvider::Notifier => self.notifier.selection_increment(),
         }
     }
     fn decrement_selection_on<T: Into<GlobalProvider>>(&mut self, id: T) -> bool {
         match id.into() {
             GlobalProvider::ContentProvider(id) => {
+                if let Some(matches) = self.filter_matches(id) {
+                    return self.move_filtered_selection(id, matches, false);
+                }
                 let cp = self.get_provider_mut(id);
                 cp.selection_decrement()
             }
-            GlobalProvider::Notifier => todo!(),
+            GlobalProvider::Notifier => self.notifier.selection_decrement(),
         }
     }
 }
@@ -7,5 +7,6 @@ mod display;
 pub mod manager;
 pub mod register;
 pub mod stack;
+pub mod filter;
 
 
@@ -0,0 +1,126 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::borrow::Cow;
+use tui::{
+    text::Span,
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        providers::{
+            traits::{
+                impliment_content_provider,
+                SongProvider,
+                Provider,
+                ContentProviderTrait,
+                YankDest,
+                SongYankDest,
+                try_paste_songs,
+            },
+        },
+        register::{
+            SongID,
+            ContentProviderID,
+        },
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            Display,
+            ListBuilder,
+        },
+    },
+    service::editors::{
+        Yank,
+        YankAction,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artist {
+    songs: Vec<SongID>,
+    pub name: Cow<'static, str>,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    index: SelectedIndex,
+}
+impl Artist {
+    pub fn new<T: Into<Cow<'static, str>>>(name: T) -> Self {
+        Self {
+            songs: Default::default(),
+            name: name.into(),
+            index: Default::default(),
+        }
+    }
+}
+
+impl SongProvider for Artist {
+    fn add_song(&mut self, id: SongID) {
+        self.songs.push(id)
+    }
+    fn songs<'a>(&'a self) -> Box<dyn Iterator<Item = &'a SongID> + 'a> {
+        Box::new(self.songs.iter())
+    }
+    fn songs_mut(&mut self) -> &mut Vec<SongID> {
+        &mut self.songs
+    }
+}
+
+impl Provider for Artist {
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.index
+    }
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.index
+    }
+}
+
+impl<'b> Display<'b> for Artist {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        let title = format!(
+            "Artist: {name}",
+            name = self.get_name(),
+        );
+        lb.title(Span::raw(title));
+
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.ids()
+                .map(|id| context.display_item(id))
+                .collect()
+            }
+            DisplayState::Menu(_) => unreachable!(),
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
+impl YankDest<SongID> for Artist {
+    fn try_paste(&mut self, items: Vec<Yank<SongID>>, start_index: Option<usize>, self_id: ContentProviderID) -> YankAction {
+        try_paste_songs(items, start_index, self.songs.len(), self_id)
+    }
+    fn dest_vec_mut(&mut self) -> Option<&mut Vec<SongID>> {
+        Some(&mut self.songs)
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for Artist {
+    impliment_content_provider!(Artist, SongProvider, Provider, Display, SongYankDest);
+}
@@ -0,0 +1,298 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::{
+    borrow::Cow,
+    collections::{
+        HashMap,
+        HashSet,
+    },
+};
+use anyhow::Result;
+use tui::{
+    text::Span,
+    style::{
+        Style,
+        Color,
+    },
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        stack::StateContext,
+        register::{
+            ContentProviderID,
+            SongID,
+        },
+        providers::{
+            traits::{
+                impliment_content_provider,
+                ContentProviderTrait,
+                CPProvider,
+                Loadable,
+                Menu,
+                Provider,
+            },
+            artist::Artist,
+        },
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            Display,
+            SelectedText,
+            Item,
+            ListBuilder,
+            Line,
+        },
+    },
+};
+
+/// groups every song in the register by its artist. each artist is a child provider holding its songs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtistProvider {
+    providers: Vec<ContentProviderID>,
+    name: Cow<'static, str>,
+    loaded: bool,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    selected: SelectedIndex,
+}
+impl Default for ArtistProvider {
+    fn default() -> Self {
+        Self {
+            providers: Default::default(),
+            name: "Artists".into(),
+            loaded: false,
+            selected: Default::default(),
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArtistProviderMenuOption {
+    RESCAN_SONGS,
+}
+
+impl ArtistProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = ArtistProviderMenuOption>> {
+        Box::new([
+            ArtistProviderMenuOption::RESCAN_SONGS,
+        ].into_iter())
+    }
+
+    fn scan_action(self_id: ContentProviderID) -> ContentManagerAction {
+        vec![
+            ContentManagerAction::Callback {
+                callback: ArtistScan { id: self_id }.into(),
+            },
+            ContentManagerAction::RefreshDisplayContent,
+        ].into()
+    }
+}
+
+impl Provider for ArtistProvider {
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.selected
+    }
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.selected
+    }
+}
+
+impl CPProvider for ArtistProvider {
+    fn add_provider(&mut self, id: ContentProviderID) {
+        self.providers.push(id);
+    }
+    fn providers<'a>(&'a self) -> Box<dyn Iterator<Item = &'a ContentProviderID> + 'a> {
+        Box::new(self.providers.iter())
+    }
+    fn providers_mut(&mut self) -> &mut Vec<ContentProviderID> {
+        &mut self.providers
+    }
+}
+
+impl Loadable for ArtistProvider {
+    fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+    fn load(&mut self, self_id: ContentProviderID) -> Result<ContentManagerAction> {
+        self.loaded = true;
+        Ok(Self::scan_action(self_id))
+    }
+}
+
+impl Menu for ArtistProvider {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, self_id: ContentProviderID) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        match option {
+            ArtistProviderMenuOption::RESCAN_SONGS => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    Self::scan_action(self_id),
+                ].into()
+            }
+        }
+    }
+}
+
+impl<'b> Display<'b> for ArtistProvider {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(self.get_name()));
+
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.ids()
+                .map(|id| context.display_item(id))
+                .collect()
+            }
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for ArtistProvider {
+    impliment_content_provider!(ArtistProvider, Provider, CPProvider, Loadable, Menu, Display);
+}
+
+
+/// goes through the songs in the saved providers (queues, playlists, library) and files the ones with an artist under the matching Artist.
+/// songs that are already under an artist are skipped, so rescanning does not add copies. songs that are only in explorers or
+/// search results are not looked at, as they are not kept
+#[derive(Debug)]
+struct ArtistScan {
+    id: ContentProviderID,
+}
+impl ContentManagerCallbackTrait for ArtistScan {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let artist_ids = ch.get_provider(self.id)
+        .as_provider()
+        .unwrap()
+        .providers()
+        .cloned()
+        .collect::<Vec<_>>();
+
+        let mut artists = HashMap::new();
+        let mut known = HashSet::new();
+        for id in artist_ids {
+            let cp = ch.get_provider(id);
+            known.extend(cp.as_song_provider().unwrap().songs().cloned());
+            artists.insert(cp.as_display().get_name().into_owned(), id);
+        }
+
+        let new_songs = persistent_songs(ch)
+        .into_iter()
+        .filter_map(|id| {
+            let artist = ch.get_song(id)
+            .as_display()
+            .artist()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())?
+            .to_owned();
+            if known.insert(id) {
+                Some((artist, id))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+        for (artist, song_id) in new_songs {
+            let artist_id = match artists.get(&artist) {
+                Some(&id) => id,
+                None => {
+                    let id = ch.alloc_content_provider(Artist::new(artist.clone()).into());
+                    ch.get_provider_mut(self.id)
+                    .as_provider_mut()
+                    .unwrap()
+                    .add_provider(id);
+                    artists.insert(artist, id);
+                    id
+                }
+            };
+            ch.register(song_id); // for being stored in the Artist
+            ch.get_provider_mut(artist_id)
+            .as_song_provider_mut()
+            .unwrap()
+            .add_song(song_id);
+        }
+
+        let mut sorted = artists.into_iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|(name, _)| name.to_lowercase());
+        *ch.get_provider_mut(self.id)
+        .as_provider_mut()
+        .unwrap()
+        .providers_mut() = sorted.into_iter().map(|(_, id)| id).collect();
+        Ok(())
+    }
+}
+
+/// every song under the providers of MainProvider that are saved in the db (except the artists themselves)
+fn persistent_songs(ch: &ContentManager) -> Vec<SongID> {
+    let mp = ch.get_main_provider();
+    let mut stack = [Some(mp.queue_provider), mp.playlist_provider, mp.library]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    let mut seen = HashSet::new();
+    let mut songs = vec![];
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        let cp = ch.get_provider(id);
+        if let Some(sp) = cp.as_song_provider() {
+            songs.extend(sp.songs().cloned());
+        }
+        if let Some(p) = cp.as_provider() {
+            stack.extend(p.providers().cloned());
+        }
+    }
+    songs
+}
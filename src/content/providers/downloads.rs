@@ -0,0 +1,469 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::borrow::Cow;
+use anyhow::Result;
+use tui::{
+    text::Span,
+    style::{
+        Color,
+        Style,
+    },
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        providers::traits::{
+            impliment_content_provider,
+            ContentProviderTrait,
+            SongProvider,
+            Provider,
+            Menu,
+        },
+        register::{
+            SongID,
+            ContentProviderID,
+        },
+        song::tagged_file_song::TaggedFileSong,
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+        stack::StateContext,
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            Display,
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+    service::{
+        config::config,
+        python::action::PyAction,
+        notifier::Severity,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JobID(u64);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobStatus {
+    Queued,
+    /// progress is from 0 to 1 (None till the download reports it)
+    Running {
+        progress: Option<f64>,
+    },
+    /// cancel was requested, but the download has not stopped yet
+    Cancelling,
+    Finished,
+    Failed(String),
+    Cancelled,
+}
+impl JobStatus {
+    fn is_active(&self) -> bool {
+        matches!(self, Self::Running {..} | Self::Cancelling)
+    }
+
+    fn is_done(&self) -> bool {
+        matches!(self, Self::Finished | Self::Failed(_) | Self::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: JobID,
+    pub status: JobStatus,
+}
+
+/// every download of this session. at most config().max_concurrent_downloads of them run at once, the rest wait in the queue.
+/// this is never saved in the db (see ContentManager::snapshot)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Downloads {
+    /// songs[i] is the song that jobs[i] downloads
+    songs: Vec<SongID>,
+    jobs: Vec<Job>,
+    next_job: u64,
+    name: Cow<'static, str>,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    index: SelectedIndex,
+}
+
+impl Downloads {
+    pub fn new() -> Self {
+        Self {
+            songs: Default::default(),
+            jobs: Default::default(),
+            next_job: 0,
+            name: "Downloads".into(),
+            index: Default::default(),
+        }
+    }
+
+    /// the song should be registered for being stored here
+    pub fn add_job(&mut self, song: SongID) -> JobID {
+        let id = JobID(self.next_job);
+        self.next_job += 1;
+        self.songs.push(song);
+        self.jobs.push(Job { id, status: JobStatus::Queued });
+        id
+    }
+
+    /// if the song is queued or being downloaded. failed and cancelled ones can be queued again
+    fn is_downloading(&self, song: SongID) -> bool {
+        self.songs.iter()
+        .zip(self.jobs.iter())
+        .any(|(&s, j)| s == song && !j.status.is_done())
+    }
+
+    fn position(&self, job: JobID) -> Option<usize> {
+        self.jobs.iter().position(|j| j.id == job)
+    }
+
+    fn set_status(&mut self, job: JobID, status: JobStatus) {
+        if let Some(i) = self.position(job) {
+            self.jobs[i].status = status;
+        }
+    }
+
+    /// marks as many queued jobs as running as the limit allows, and returns them with their songs
+    fn start_next(&mut self) -> Vec<(JobID, SongID)> {
+        let running = self.jobs.iter().filter(|j| j.status.is_active()).count();
+        let free = config().max_concurrent_downloads.saturating_sub(running);
+        self.jobs
+        .iter_mut()
+        .zip(self.songs.iter())
+        .filter(|(j, _)| j.status == JobStatus::Queued)
+        .take(free)
+        .map(|(j, &s)| {
+            j.status = JobStatus::Running { progress: None };
+            (j.id, s)
+        })
+        .collect()
+    }
+
+    fn selected_job(&self) -> Option<&Job> {
+        self.jobs.get(self.index.selected_index())
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = DownloadsMenuOption>> {
+        let status = self.selected_job().map(|j| j.status.clone());
+        let any_failed = self.jobs.iter().any(|j| matches!(j.status, JobStatus::Failed(_)));
+        let any_done = self.jobs.iter().any(|j| j.status.is_done());
+        Box::new([
+            (DownloadsMenuOption::CANCEL, matches!(status, Some(JobStatus::Queued | JobStatus::Running {..}))),
+            (DownloadsMenuOption::RETRY, matches!(status, Some(JobStatus::Failed(_) | JobStatus::Cancelled))),
+            (DownloadsMenuOption::RETRY_ALL_FAILED, any_failed),
+            (DownloadsMenuOption::CLEAR_DONE, any_done),
+        ].into_iter().filter(|(_, show)| *show).map(|(o, _)| o))
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DownloadsMenuOption {
+    CANCEL,
+    RETRY,
+    RETRY_ALL_FAILED,
+    CLEAR_DONE,
+}
+
+impl SongProvider for Downloads {
+    fn add_song(&mut self, id: SongID) {
+        self.add_job(id);
+    }
+    fn songs<'a>(&'a self) -> Box<dyn Iterator<Item = &'a SongID> + 'a> {
+        Box::new(self.songs.iter())
+    }
+    fn songs_mut(&mut self) -> &mut Vec<SongID> {
+        &mut self.songs
+    }
+}
+
+impl Provider for Downloads {
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.index
+    }
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.index
+    }
+}
+
+impl Menu for Downloads {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, _: ContentProviderID) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        let selected = self.index.selected_index();
+        let mut actions = vec![ContentManagerAction::PopContentStack];
+        match option {
+            DownloadsMenuOption::CANCEL => {
+                let job = &mut self.jobs[selected];
+                match job.status {
+                    JobStatus::Queued => {
+                        job.status = JobStatus::Cancelled;
+                    }
+                    JobStatus::Running {..} => {
+                        job.status = JobStatus::Cancelling;
+                        actions.push(PyAction::CancelJob { job: job.id }.into());
+                    }
+                    _ => (),
+                }
+            }
+            DownloadsMenuOption::RETRY => {
+                self.jobs[selected].status = JobStatus::Queued;
+                actions.push(start_action());
+            }
+            DownloadsMenuOption::RETRY_ALL_FAILED => {
+                self.jobs
+                .iter_mut()
+                .filter(|j| matches!(j.status, JobStatus::Failed(_)))
+                .for_each(|j| j.status = JobStatus::Queued);
+                actions.push(start_action());
+            }
+            DownloadsMenuOption::CLEAR_DONE => {
+                let (done, kept): (Vec<_>, Vec<_>) = self.jobs
+                .drain(..)
+                .zip(self.songs.drain(..))
+                .partition(|(j, _)| j.status.is_done());
+                let (jobs, songs) = kept.into_iter().unzip();
+                self.jobs = jobs;
+                self.songs = songs;
+                let len = self.jobs.len();
+                if self.index.selected_index() >= len {
+                    self.index.select(len.saturating_sub(1));
+                }
+                actions.push(ContentManagerAction::Unregister {
+                    ids: done.into_iter().map(|(_, s)| s.into()).collect(),
+                });
+            }
+        }
+        actions.push(ContentManagerAction::RefreshDisplayContent);
+        actions.into()
+    }
+}
+
+impl<'b> Display<'b> for Downloads {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(self.get_name()));
+
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.jobs
+                .iter()
+                .zip(self.songs.iter())
+                .map(|(j, &s)| {
+                    let title = context.songs.get(s).unwrap().as_display().title().to_owned();
+                    let status = match &j.status {
+                        JobStatus::Queued => "queued".to_owned(),
+                        JobStatus::Running { progress: Some(p) } => format!("{:>3.0}%", p * 100.0),
+                        JobStatus::Running { progress: None } => "starting".to_owned(),
+                        JobStatus::Cancelling => "cancelling".to_owned(),
+                        JobStatus::Finished => "done".to_owned(),
+                        JobStatus::Failed(err) => format!("failed: {err}"),
+                        JobStatus::Cancelled => "cancelled".to_owned(),
+                    };
+                    format!("[{status}] {title}")
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+    fn get_name(&self) -> Cow<'static, str> {
+        let active = self.jobs.iter().filter(|j| !j.status.is_done()).count();
+        match active {
+            0 => self.name.clone(),
+            n => format!("{} ({n})", self.name).into(),
+        }
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for Downloads {
+    impliment_content_provider!(Downloads, SongProvider, Provider, Menu, Display);
+}
+
+fn downloads_mut(ch: &mut ContentManager) -> Option<&mut Downloads> {
+    let id = ch.downloads?;
+    ch.get_provider_mut(id).as_any_mut().downcast_mut::<Downloads>()
+}
+
+/// adds download jobs for the online songs among these and starts as many as allowed
+pub fn download_action(songs: Vec<SongID>) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: QueueDownloads { songs }.into(),
+    }
+}
+
+fn start_action() -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: StartDownloads.into(),
+    }
+}
+
+pub fn progress_action(job: JobID, progress: f64) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: DownloadProgress { job, progress }.into(),
+    }
+}
+
+/// the downloaded song (or why it failed)
+pub fn finished_action(job: JobID, res: Result<TaggedFileSong>) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: DownloadFinished { job, res }.into(),
+    }
+}
+
+#[derive(Debug)]
+struct QueueDownloads {
+    songs: Vec<SongID>,
+}
+impl ContentManagerCallbackTrait for QueueDownloads {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let mut songs = self.songs
+        .into_iter()
+        .filter(|&s| ch.get_song(s).is_online())
+        .collect::<Vec<_>>();
+        if let Some(d) = downloads_mut(ch) {
+            songs.retain(|&s| !d.is_downloading(s));
+        }
+        if songs.is_empty() {
+            ch.notify(Severity::Warning, "nothing to download");
+            return Ok(());
+        }
+        let id = ch.get_or_add_downloads();
+        for &s in songs.iter() {
+            ch.register(s); // for being stored in Downloads
+            ch.get_provider_mut(id)
+            .as_any_mut()
+            .downcast_mut::<Downloads>()
+            .unwrap()
+            .add_job(s);
+        }
+        ch.notify(Severity::Info, format!("queued {} downloads", songs.len()));
+        start_action().apply(ch)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct StartDownloads;
+impl ContentManagerCallbackTrait for StartDownloads {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let started = match downloads_mut(ch) {
+            Some(d) => d.start_next(),
+            None => return Ok(()),
+        };
+        for (job, song) in started {
+            match ch.get_song(song).save_to_path(job) {
+                Ok(action) => action.apply(ch)?,
+                Err(err) => finished_action(job, Err(err)).apply(ch)?,
+            }
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct DownloadProgress {
+    job: JobID,
+    progress: f64,
+}
+impl ContentManagerCallbackTrait for DownloadProgress {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        if let Some(d) = downloads_mut(ch) {
+            if let Some(i) = d.position(self.job) {
+                if let JobStatus::Running { progress } = &mut d.jobs[i].status {
+                    *progress = Some(self.progress.clamp(0.0, 1.0));
+                }
+            }
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+/// the downloaded file takes the place of the online song, so it changes in every queue and playlist it is in
+#[derive(Debug)]
+struct DownloadFinished {
+    job: JobID,
+    res: Result<TaggedFileSong>,
+}
+impl ContentManagerCallbackTrait for DownloadFinished {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let Self { job, res } = *self;
+        let d = match downloads_mut(ch) {
+            Some(d) => d,
+            None => return Ok(()),
+        };
+        let i = match d.position(job) {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+        let song_id = d.songs[i];
+        let cancelled = d.jobs[i].status == JobStatus::Cancelling;
+        match res {
+            Ok(song) => {
+                d.set_status(job, JobStatus::Finished);
+                ch.notify(Severity::Info, format!("downloaded {}", song.title()));
+                *ch.get_song_mut(song_id) = song.into();
+            }
+            Err(_) if cancelled => {
+                d.set_status(job, JobStatus::Cancelled);
+            }
+            Err(err) => {
+                // python errors are whole tracebacks
+                let err = err.to_string();
+                let err = err.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("").trim().to_owned();
+                d.set_status(job, JobStatus::Failed(err.clone()));
+                let title = ch.get_song(song_id).as_display().title().to_owned();
+                ch.notify(Severity::Error, format!("could not download {title}: {err}"));
+            }
+        }
+        start_action().apply(ch)?;
+        Ok(())
+    }
+}
@@ -6,7 +6,10 @@ use crate::{
     error,
 };
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    path::Path,
+};
 use tui::{
     text::Span,
     style::{
@@ -21,6 +24,7 @@ use serde::{Serialize, Deserialize};
 use crate::{
     content::{
         song::{
+            Song,
             tagged_file_song::TaggedFileSong,
             untagged_file_song::UntaggedFileSong,
         },
@@ -52,6 +56,7 @@ use crate::{
     },
     app::{
         app::SelectedIndex,
+        action::TypingCallback,
         display::{
             Display,
             Line,
@@ -60,6 +65,7 @@ use crate::{
             ListBuilder,
         },
     },
+    service::watcher,
 };
 
 
@@ -92,6 +98,13 @@ impl FileExplorer {
         fe
     }
 
+    /// forgets the entries, so the dir is read (and watched) again the next time the explorer is opened.
+    /// returns the ids it held, which still need to be unregistered
+    pub fn unload(&mut self) -> Vec<ID> {
+        self.loaded = false;
+        self.pop_all_ids()
+    }
+
     fn pop_all_ids(&mut self) -> Vec<ID> {
         let songs = std::mem::replace(&mut self.songs, Default::default());
         let providers = std::mem::replace(&mut self.providers, Default::default());
@@ -169,31 +182,10 @@ impl Loadable for FileExplorer {
         .filter_map(|e| e.ok())
         .map(|e| e.path())
         .map(|e| {
-            if e.is_dir() {
-                let dir = e.to_str().unwrap();
-                sp.push(FileExplorer {
-                    name: Cow::from(dir.rsplit_terminator("/").next().unwrap().to_owned()),
-                    path: Cow::from(dir.to_owned()),
-                    child: true,
-                    ..Default::default()
-                }.into());
-            } else if e.is_file() {
-                let file_path = e.to_str().unwrap();
-                let file = Probe::open(file_path)?; // file open error
-                match file.guess_file_type() {
-                    Ok(fpt) => { // FIX: this does not mean this is some kinda song???
-                        dbg!(fpt.file_type());
-                        match TaggedFileSong::from_file_path(file_path.into()) {
-                            Ok(Some(song)) => {
-                                s.push(song.into());
-                            }
-                            _ => {
-                                s.push(UntaggedFileSong::from_file_path(file_path.into()).into())
-                            }
-                        }
-                    }
-                    Err(_) => (),
-                }
+            match FsEntry::read(&e)? {
+                Some(FsEntry::Song(song)) => s.push(song),
+                Some(FsEntry::Explorer(fe)) => sp.push(fe.into()),
+                None => (),
             }
             Ok(())
         })
@@ -208,12 +200,54 @@ impl Loadable for FileExplorer {
         // .collect::<Result<_>>()?;
         let action = vec![
             ContentManagerAction::LoadContentProvider {songs: s, content_providers: sp, loader_id: id},
+            watcher::watch_action(path.into_owned()),
             ContentManagerAction::RefreshDisplayContent,
         ].into();
         Ok(action)
     }
 }
 
+/// something in a directory that can be shown in a FileExplorer
+#[derive(Debug, Clone)]
+pub enum FsEntry {
+    Song(Song),
+    Explorer(FileExplorer),
+}
+impl FsEntry {
+    /// None if the path is neither a directory nor a song
+    pub fn read(e: &Path) -> Result<Option<Self>> {
+        let entry = if e.is_dir() {
+            let dir = e.to_str().unwrap();
+            let fe = FileExplorer {
+                name: Cow::from(dir.rsplit_terminator("/").next().unwrap().to_owned()),
+                path: Cow::from(dir.to_owned()),
+                child: true,
+                ..Default::default()
+            };
+            Some(Self::Explorer(fe))
+        } else if e.is_file() {
+            let file_path = e.to_str().unwrap();
+            let file = Probe::open(file_path)?; // file open error
+            match file.guess_file_type() {
+                Ok(fpt) => { // FIX: this does not mean this is some kinda song???
+                    match TaggedFileSong::from_file_path(file_path.into()) {
+                        Ok(Some(song)) => {
+                            Some(Self::Song(song.into()))
+                        }
+                        _ => {
+                            Some(Self::Song(UntaggedFileSong::from_file_path(file_path.into()).into()))
+                        }
+                    }
+                }
+                Err(_) => None,
+            }
+        } else {
+            None
+        };
+        Ok(entry)
+    }
+}
+
 impl<'b> Display<'b> for FileExplorer {
     type DisplayContext = DisplayContext<'b>;
     fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
@@ -266,19 +300,21 @@ impl Editable for FileExplorer {
                     ContentManagerAction::EnableTyping {
                         content: self.path.as_ref().to_owned(),
                         loader: self_id.into(),
-                        callback: Box::new(move |me: &mut ContentProvider, content: String| {
+                        callback: TypingCallback::ContentProvider(Box::new(move |me: &mut ContentProvider, content: String| {
                             let cp = me.as_any_mut().downcast_mut::<Self>().unwrap();
                             let ids = cp.pop_all_ids();
+                            let old_dir = cp.path.clone().into_owned();
                             *me = Self::new(content.into()).into();
                             vec![
                                 ContentManagerAction::Unregister {
                                     ids,
                                 },
+                                watcher::unwatch_action(old_dir),
                                 ContentManagerAction::PopContentStack, // typing
                                 ContentManagerAction::PopContentStack, // edit
                                 ContentManagerAction::TryLoadContentProvider { loader_id: self_id },
                             ].into()
-                        }),
+                        })),
                     },
                 ].into()
             }
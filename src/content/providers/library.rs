@@ -0,0 +1,288 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::{
+    borrow::Cow,
+    collections::{
+        HashMap,
+        HashSet,
+    },
+};
+use anyhow::Result;
+use derivative::Derivative;
+use tui::{
+    text::Span,
+    style::{
+        Color,
+        Style,
+    },
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        providers::traits::{
+            impliment_content_provider,
+            ContentProviderTrait,
+            SongProvider,
+            Provider,
+            Loadable,
+            Menu,
+        },
+        register::{
+            SongID,
+            ContentProviderID,
+        },
+        song::Song,
+        manager::{
+            action::{
+                ContentManagerAction,
+                RustParallelAction,
+            },
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+        stack::StateContext,
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            Display,
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+    service::{
+        config::config,
+        db::library_index::LibraryIndex,
+        notifier::Severity,
+    },
+};
+
+/// every song in config().music_path. the songs are saved in the db like any other provider,
+/// but the library is rescanned once per session (in the background) using the index at config().library_index_path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Library {
+    songs: Vec<SongID>,
+    name: Cow<'static, str>,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    index: SelectedIndex,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    loaded: bool,
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Self {
+            songs: Default::default(),
+            name: "Library".into(),
+            index: Default::default(),
+            loaded: false,
+        }
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = LibraryMenuOption>> {
+        Box::new([
+            LibraryMenuOption::RESCAN,
+        ].into_iter())
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LibraryMenuOption {
+    RESCAN,
+}
+
+impl SongProvider for Library {
+    fn add_song(&mut self, id: SongID) {
+        self.songs.push(id)
+    }
+    fn songs<'a>(&'a self) -> Box<dyn Iterator<Item = &'a SongID> + 'a> {
+        Box::new(self.songs.iter())
+    }
+    fn songs_mut(&mut self) -> &mut Vec<SongID> {
+        &mut self.songs
+    }
+}
+
+impl Provider for Library {
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.index
+    }
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.index
+    }
+}
+
+impl Loadable for Library {
+    fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    fn load(&mut self, id: ContentProviderID) -> Result<ContentManagerAction> {
+        self.loaded = true;
+        let action = RustParallelAction::Callback {
+            callback: Box::new(move || {
+                let index_path = &config().library_index_path;
+                let mut index = LibraryIndex::load(index_path)?;
+                let stats = index.scan(&config().music_path)?;
+                index.save(index_path)?;
+
+                let mut songs = index.entries
+                .iter()
+                .map(|(path, e)| (path.clone(), e.song(path)))
+                .collect::<Vec<_>>();
+                songs.sort_by(|a, b| a.0.cmp(&b.0));
+                let action = ContentManagerAction::Callback {
+                    callback: LibraryScanned {
+                        id,
+                        songs,
+                        changed: stats.changed,
+                        removed: stats.removed,
+                    }.into(),
+                };
+                Ok(action.into())
+            }),
+        }.into();
+        Ok(action)
+    }
+}
+
+impl Menu for Library {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, self_id: ContentProviderID) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        match option {
+            LibraryMenuOption::RESCAN => {
+                self.loaded = false;
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    ContentManagerAction::TryLoadContentProvider { loader_id: self_id },
+                ].into()
+            }
+        }
+    }
+}
+
+impl<'b> Display<'b> for Library {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(self.get_name()));
+
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.ids()
+                .map(|id| context.display_item(id))
+                .collect()
+            }
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for Library {
+    impliment_content_provider!(Library, SongProvider, Provider, Loadable, Menu, Display);
+}
+
+/// songs that did not change keep their ids (so they stay the same songs in queues and playlists),
+/// changed songs are replaced in place and removed songs are dropped from the library
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct LibraryScanned {
+    id: ContentProviderID,
+    #[derivative(Debug="ignore")]
+    songs: Vec<(String, Song)>,
+    #[derivative(Debug="ignore")]
+    changed: HashSet<String>,
+    removed: usize,
+}
+impl ContentManagerCallbackTrait for LibraryScanned {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let Self { id, songs, changed, removed } = *self;
+        let old_songs = std::mem::take(
+            ch.get_provider_mut(id)
+            .as_song_provider_mut()
+            .unwrap()
+            .songs_mut()
+        );
+        let mut by_path = HashMap::new();
+        for song_id in old_songs {
+            match ch.get_song(song_id).file_path().map(String::from) {
+                Some(path) => {
+                    by_path.insert(path, song_id);
+                }
+                None => ch.unregister(song_id),
+            }
+        }
+
+        let mut added = 0;
+        let mut updated = 0;
+        let songs = songs
+        .into_iter()
+        .map(|(path, song)| {
+            match by_path.remove(&path) {
+                Some(song_id) => {
+                    if changed.contains(&path) {
+                        *ch.get_song_mut(song_id) = song;
+                        updated += 1;
+                    }
+                    song_id
+                }
+                None => {
+                    added += 1;
+                    ch.alloc_song(song) // for being stored in Library
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+        by_path.into_values().for_each(|song_id| ch.unregister(song_id));
+
+        let library = ch.get_provider_mut(id);
+        *library.as_song_provider_mut().unwrap().songs_mut() = songs;
+        let len = library.get_size();
+        let index = library.get_selected_index_mut();
+        if index.selected_index() >= len {
+            index.select(len.saturating_sub(1));
+        }
+
+        ch.notify(Severity::Info, format!("library scanned: {added} new, {updated} updated, {removed} removed"));
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
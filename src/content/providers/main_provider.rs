@@ -1,6 +1,6 @@
 
 use std::borrow::Cow;
-
+use anyhow::Result;
 use tui::{
     text::{
         Span,
@@ -14,7 +14,11 @@ use serde::{Serialize, Deserialize};
 
 use crate::{
     content::{
-        manager::action::ContentManagerAction,
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
         stack::StateContext,
         register::ContentProviderID,
         providers::{
@@ -29,6 +33,11 @@ use crate::{
             file_explorer::FileExplorer,
             yt_explorer::YTExplorer,
             queue_provider::QueueProvider,
+            artist_provider::ArtistProvider,
+            playlist_provider::PlaylistProvider,
+            playlist::Playlist,
+            library::Library,
+            search_results,
         },
         display::{
             DisplayContext,
@@ -37,6 +46,7 @@ use crate::{
     },
     app::{
         app::SelectedIndex,
+        action::TypingCallback,
         display::{
             Display,
             SelectedText,
@@ -45,7 +55,10 @@ use crate::{
             Line,
         },
     },
-    service::config::config,
+    service::{
+        config::config,
+        db::musimanager_db,
+    },
 };
 
 
@@ -55,7 +68,12 @@ pub struct MainProvider {
     providers: Vec<ContentProviderID>,
     pub queue_provider: ContentProviderID,
 
-    // pub artist_provider: ContentProviderID,
+    #[serde(default)]
+    pub artist_provider: Option<ContentProviderID>,
+    #[serde(default)]
+    pub playlist_provider: Option<ContentProviderID>,
+    #[serde(default)]
+    pub library: Option<ContentProviderID>,
     name: Cow<'static, str>,
 
     // https://serde.rs/attr-default.html
@@ -74,7 +92,9 @@ impl MainProvider {
             name: Cow::from("main"),
             selected: Default::default(),
             queue_provider,
-            // artist_provider: alloc(),
+            artist_provider: None,
+            playlist_provider: None,
+            library: None,
         };
 
         mp.load(alloc, register);
@@ -83,21 +103,38 @@ impl MainProvider {
 
     pub fn load(&mut self, mut alloc: impl FnMut(ContentProvider) -> ContentProviderID, mut register: impl FnMut(ContentProviderID)) {
         register(self.queue_provider);
-        self.providers = vec![
-            self.queue_provider,
+        self.providers = vec![self.queue_provider];
+        if let Some(id) = self.artist_provider {
+            register(id);
+            self.providers.push(id);
+        }
+        if let Some(id) = self.playlist_provider {
+            register(id);
+            self.providers.push(id);
+        }
+        if let Some(id) = self.library {
+            register(id);
+            self.providers.push(id);
+        }
+        self.providers.extend([
             alloc(FileExplorer::new(config().file_explorer_default_path.to_str().unwrap().into()).into()),
             alloc(YTExplorer::new().into()),
-        ];
+        ]);
     }
 }
 
 #[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum MainProviderMenuOption {
+    SEARCH_LIBRARY,
+    ADD_LIBRARY,
     ADD_ARTIST_PROVIDER,
     ADD_PLAYLIST_PROVIDER,
+    NEW_PLAYLIST,
     ADD_FILE_EXPLORER,
     ADD_YT_EXPLORER,
+    IMPORT_MUSIMANAGER_DB,
+    EXPORT_MUSIMANAGER_DB,
 }
 
 impl CPProvider for MainProvider {
@@ -120,8 +157,54 @@ impl Menu for MainProvider {
     fn apply_option(&mut self, ctx: &mut StateContext, self_id: ContentProviderID) -> ContentManagerAction {
         let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
         match option {
-            MainProviderMenuOption::ADD_ARTIST_PROVIDER => todo!(),
-            MainProviderMenuOption::ADD_PLAYLIST_PROVIDER => todo!(),
+            MainProviderMenuOption::SEARCH_LIBRARY => {
+                let mut index = SelectedIndex::default();
+                index.select(ctx.last().selected_index());
+                ctx.push(index);
+                ContentManagerAction::EnableTyping {
+                    content: "".into(),
+                    loader: self_id.into(),
+                    callback: TypingCallback::ContentProvider(Box::new(|_: &mut ContentProvider, query: String| {
+                        vec![
+                            ContentManagerAction::PopContentStack, // typing
+                            ContentManagerAction::PopContentStack, // menu
+                            search_results::search_action(query),
+                        ].into()
+                    })),
+                }
+            }
+            MainProviderMenuOption::ADD_LIBRARY => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    ContentManagerAction::Callback {
+                        callback: AddLibrary.into(),
+                    },
+                ].into()
+            }
+            MainProviderMenuOption::ADD_ARTIST_PROVIDER => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    ContentManagerAction::Callback {
+                        callback: AddArtistProvider.into(),
+                    },
+                ].into()
+            }
+            MainProviderMenuOption::ADD_PLAYLIST_PROVIDER => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    ContentManagerAction::Callback {
+                        callback: AddPlaylist { new_playlist: false }.into(),
+                    },
+                ].into()
+            }
+            MainProviderMenuOption::NEW_PLAYLIST => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    ContentManagerAction::Callback {
+                        callback: AddPlaylist { new_playlist: true }.into(),
+                    },
+                ].into()
+            }
             MainProviderMenuOption::ADD_FILE_EXPLORER => {
                 vec![
                     ContentManagerAction::PopContentStack,
@@ -140,6 +223,38 @@ impl Menu for MainProvider {
                     },
                 ].into()
             }
+            MainProviderMenuOption::IMPORT_MUSIMANAGER_DB => {
+                let mut index = SelectedIndex::default();
+                index.select(ctx.last().selected_index());
+                ctx.push(index);
+                ContentManagerAction::EnableTyping {
+                    content: "".into(),
+                    loader: self_id.into(),
+                    callback: TypingCallback::ContentProvider(Box::new(|_: &mut ContentProvider, path: String| {
+                        vec![
+                            ContentManagerAction::PopContentStack, // typing
+                            ContentManagerAction::PopContentStack, // menu
+                            musimanager_db::import_action(path),
+                        ].into()
+                    })),
+                }
+            }
+            MainProviderMenuOption::EXPORT_MUSIMANAGER_DB => {
+                let mut index = SelectedIndex::default();
+                index.select(ctx.last().selected_index());
+                ctx.push(index);
+                ContentManagerAction::EnableTyping {
+                    content: "".into(),
+                    loader: self_id.into(),
+                    callback: TypingCallback::ContentProvider(Box::new(|_: &mut ContentProvider, path: String| {
+                        vec![
+                            ContentManagerAction::PopContentStack, // typing
+                            ContentManagerAction::PopContentStack, // menu
+                            musimanager_db::export_action(path),
+                        ].into()
+                    })),
+                }
+            }
         }
     }
 }
@@ -200,11 +315,106 @@ impl ContentProviderTrait for MainProvider {
 impl MainProvider {
     // TODO: fix
     fn menu(&self, ctx: &StateContext) -> Box<dyn Iterator<Item = MainProviderMenuOption>> {
+        let has_artist_provider = self.artist_provider.is_some();
+        let has_playlist_provider = self.playlist_provider.is_some();
+        let has_library = self.library.is_some();
         Box::new([
+            MainProviderMenuOption::SEARCH_LIBRARY,
+            MainProviderMenuOption::ADD_LIBRARY,
             MainProviderMenuOption::ADD_ARTIST_PROVIDER,
             MainProviderMenuOption::ADD_PLAYLIST_PROVIDER,
+            MainProviderMenuOption::NEW_PLAYLIST,
             MainProviderMenuOption::ADD_FILE_EXPLORER,
             MainProviderMenuOption::ADD_YT_EXPLORER,
-        ].into_iter())
+            MainProviderMenuOption::IMPORT_MUSIMANAGER_DB,
+            MainProviderMenuOption::EXPORT_MUSIMANAGER_DB,
+        ].into_iter()
+        .filter(move |o| !(has_artist_provider && *o == MainProviderMenuOption::ADD_ARTIST_PROVIDER)) // only one artist provider is needed
+        .filter(move |o| !(has_playlist_provider && *o == MainProviderMenuOption::ADD_PLAYLIST_PROVIDER))
+        .filter(move |o| !(has_library && *o == MainProviderMenuOption::ADD_LIBRARY)))
+    }
+}
+
+/// the artist provider is saved in MainProvider (just like the QueueProvider) so it survives restarts
+#[derive(Debug)]
+struct AddArtistProvider;
+impl ContentManagerCallbackTrait for AddArtistProvider {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        if ch.get_main_provider().artist_provider.is_some() {
+            return Ok(());
+        }
+        let id = get_or_add_artist_provider(ch);
+        ContentManagerAction::PushToContentStack { id: id.into() }.apply(ch)?;
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+/// the library is saved in MainProvider too. there is only one, and it always indexes config().music_path
+#[derive(Debug)]
+struct AddLibrary;
+impl ContentManagerCallbackTrait for AddLibrary {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let id = get_or_add_library(ch);
+        ContentManagerAction::PushToContentStack { id: id.into() }.apply(ch)?;
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+/// the playlist provider is also saved in MainProvider. it is created on demand when the first playlist is made
+#[derive(Debug)]
+struct AddPlaylist {
+    new_playlist: bool,
+}
+impl ContentManagerCallbackTrait for AddPlaylist {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let pp_id = get_or_add_playlist_provider(ch);
+        if self.new_playlist {
+            ContentManagerAction::AddCPToCPAndContentStack {
+                id: pp_id,
+                cp: Playlist::new("new playlist").into(),
+            }.apply(ch)?;
+        } else {
+            ContentManagerAction::PushToContentStack { id: pp_id.into() }.apply(ch)?;
+            ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn get_or_add_artist_provider(ch: &mut ContentManager) -> ContentProviderID {
+    if let Some(id) = ch.get_main_provider().artist_provider {
+        return id;
+    }
+    let id = ch.alloc_content_provider(ArtistProvider::new().into()); // saved in MainProvider.artist_provider
+    ch.register(id); // for being stored in MainProvider.providers
+    let mp = ch.get_main_provider_mut();
+    mp.artist_provider = Some(id);
+    mp.add_provider(id);
+    id
+}
+
+pub fn get_or_add_playlist_provider(ch: &mut ContentManager) -> ContentProviderID {
+    if let Some(id) = ch.get_main_provider().playlist_provider {
+        return id;
+    }
+    let id = ch.alloc_content_provider(PlaylistProvider::new().into()); // saved in MainProvider.playlist_provider
+    ch.register(id); // for being stored in MainProvider.providers
+    let mp = ch.get_main_provider_mut();
+    mp.playlist_provider = Some(id);
+    mp.add_provider(id);
+    id
+}
+
+pub fn get_or_add_library(ch: &mut ContentManager) -> ContentProviderID {
+    if let Some(id) = ch.get_main_provider().library {
+        return id;
     }
+    let id = ch.alloc_content_provider(Library::new().into()); // saved in MainProvider.library
+    ch.register(id); // for being stored in MainProvider.providers
+    let mp = ch.get_main_provider_mut();
+    mp.library = Some(id);
+    mp.add_provider(id);
+    id
 }
@@ -7,6 +7,14 @@ pub mod ytalbum;
 pub mod ytplaylist;
 pub mod queue_provider;
 pub mod queue;
+pub mod artist_provider;
+pub mod artist;
+pub mod playlist_provider;
+pub mod playlist;
+pub mod search_results;
+pub mod library;
+pub mod tag_preview;
+pub mod downloads;
 
 use serde::{Serialize, Deserialize};
 
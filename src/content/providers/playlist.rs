@@ -0,0 +1,296 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::borrow::Cow;
+use anyhow::Result;
+use rand::seq::SliceRandom;
+use tui::{
+    text::Span,
+    style::{
+        Style,
+        Color,
+    },
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        stack::StateContext,
+        providers::{
+            ContentProvider,
+            traits::{
+                impliment_content_provider,
+                SongProvider,
+                Provider,
+                Menu,
+                ContentProviderTrait,
+                YankDest,
+                SongYankDest,
+                try_paste_songs,
+            },
+        },
+        register::{
+            SongID,
+            ContentProviderID,
+        },
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+    },
+    app::{
+        app::SelectedIndex,
+        action::TypingCallback,
+        display::{
+            Display,
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+    service::editors::{
+        Yank,
+        YankAction,
+        Yanker,
+        YankType,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Playlist {
+    songs: Vec<SongID>,
+    pub name: Cow<'static, str>,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    index: SelectedIndex,
+}
+impl Playlist {
+    pub fn new<T: Into<Cow<'static, str>>>(name: T) -> Self {
+        Self {
+            songs: Default::default(),
+            name: name.into(),
+            index: Default::default(),
+        }
+    }
+
+    /// panics is cp is not a SongProvider
+    pub fn from_provider(cp: &ContentProvider, register: impl FnMut(SongID)) -> Self {
+        let songs = cp
+        .as_song_provider()
+        .unwrap()
+        .songs()
+        .cloned()
+        .collect::<Vec<_>>();
+        songs.iter().cloned().for_each(register);
+        Self {
+            songs,
+            name: cp.as_display().get_name(),
+            index: Default::default(),
+        }
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = PlaylistMenuOption>> {
+        Box::new([
+            PlaylistMenuOption::PLAY,
+            PlaylistMenuOption::SHUFFLE,
+            PlaylistMenuOption::RENAME,
+            PlaylistMenuOption::DELETE,
+        ].into_iter())
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PlaylistMenuOption {
+    PLAY,
+    SHUFFLE,
+    RENAME,
+    DELETE,
+}
+
+impl SongProvider for Playlist {
+    fn add_song(&mut self, id: SongID) {
+        self.songs.push(id)
+    }
+    fn songs<'a>(&'a self) -> Box<dyn Iterator<Item = &'a SongID> + 'a> {
+        Box::new(self.songs.iter())
+    }
+    fn songs_mut(&mut self) -> &mut Vec<SongID> {
+        &mut self.songs
+    }
+}
+
+impl Provider for Playlist {
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.index
+    }
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.index
+    }
+}
+
+impl Menu for Playlist {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, self_id: ContentProviderID) -> ContentManagerAction {
+        let i = ctx.last().selected_index();
+        let option = self.menu(ctx).skip(i).next().unwrap();
+        match option {
+            PlaylistMenuOption::PLAY => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    ContentManagerAction::Callback {
+                        callback: PlayPlaylist { id: self_id }.into(),
+                    },
+                ].into()
+            }
+            PlaylistMenuOption::SHUFFLE => {
+                self.songs.shuffle(&mut rand::thread_rng());
+                ContentManagerAction::PopContentStack
+            }
+            PlaylistMenuOption::RENAME => {
+                let mut index = SelectedIndex::default();
+                index.select(i);
+                ctx.push(index);
+                ContentManagerAction::EnableTyping {
+                    content: self.name.as_ref().to_owned(),
+                    loader: self_id.into(),
+                    callback: TypingCallback::ContentProvider(Box::new(move |me: &mut ContentProvider, content: String| {
+                        let cp = me.as_any_mut().downcast_mut::<Self>().unwrap();
+                        cp.name = content.into();
+                        vec![
+                            ContentManagerAction::PopContentStack, // typing
+                            ContentManagerAction::PopContentStack, // menu
+                        ].into()
+                    })),
+                }
+            }
+            PlaylistMenuOption::DELETE => {
+                vec![
+                    ContentManagerAction::PopContentStack, // menu
+                    ContentManagerAction::Callback {
+                        callback: DeletePlaylist { id: self_id }.into(),
+                    },
+                ].into()
+            }
+        }
+    }
+}
+
+impl<'b> Display<'b> for Playlist {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        let title = format!(
+            "Playlist: {name}",
+            name = self.get_name(),
+        );
+        lb.title(Span::raw(title));
+
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.ids()
+                .map(|id| context.display_item(id))
+                .collect()
+            }
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
+impl YankDest<SongID> for Playlist {
+    fn try_paste(&mut self, items: Vec<Yank<SongID>>, start_index: Option<usize>, self_id: ContentProviderID) -> YankAction {
+        try_paste_songs(items, start_index, self.songs.len(), self_id)
+    }
+    fn dest_vec_mut(&mut self) -> Option<&mut Vec<SongID>> {
+        Some(&mut self.songs)
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for Playlist {
+    impliment_content_provider!(Playlist, SongProvider, Provider, Menu, Display, SongYankDest);
+}
+
+
+#[derive(Debug)]
+struct PlayPlaylist {
+    id: ContentProviderID,
+}
+impl ContentManagerCallbackTrait for PlayPlaylist {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let first = ch.get_provider(self.id)
+        .as_song_provider()
+        .unwrap()
+        .songs()
+        .next()
+        .cloned();
+        if let Some(song_id) = first {
+            ch.play_song(song_id)?;
+            ch.set_queue(self.id, song_id);
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+/// cuts the playlist from the PlaylistProvider (so that it can be undone/pasted like any other cut) and removes it from the top of the content stack.
+/// anything that was yanked before is dropped
+#[derive(Debug)]
+struct DeletePlaylist {
+    id: ContentProviderID,
+}
+impl ContentManagerCallbackTrait for DeletePlaylist {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        if let Some(pp) = ch.get_main_provider().playlist_provider {
+            let index = ch.get_provider(pp)
+            .as_provider()
+            .unwrap()
+            .providers()
+            .position(|&id| id == self.id);
+            if let Some(index) = index {
+                ch.edit_manager.yanker = Some(Yanker::new(pp, self.id, index));
+                let action = ch.edit_manager.apply_yank(YankType::Cut);
+                if !action.apply(ch)? {
+                    let _ = ch.edit_manager.yanker.take();
+                }
+            }
+        }
+        if ch.content_stack.last() == self.id.into() {
+            ContentManagerAction::PopContentStack.apply(ch)?;
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::borrow::Cow;
+use tui::{
+    text::Span,
+    style::{
+        Style,
+        Color,
+    },
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        stack::StateContext,
+        register::{
+            ContentProviderID,
+            SongID,
+        },
+        providers::{
+            traits::{
+                impliment_content_provider,
+                ContentProviderTrait,
+                Provider,
+                CPProvider,
+                Menu,
+                YankDest,
+                CPYankDest,
+                YankContext,
+                try_paste_providers,
+            },
+            playlist::Playlist,
+        },
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+        manager::{
+            action::ContentManagerAction,
+        },
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            Display,
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+    service::editors::{
+        YankAction,
+        Yank,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistProvider {
+    providers: Vec<ContentProviderID>,
+    name: Cow<'static, str>,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    selected: SelectedIndex,
+}
+impl Default for PlaylistProvider {
+    fn default() -> Self {
+        Self {
+            providers: Default::default(),
+            selected: Default::default(),
+            name: "Playlists".into(),
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PlaylistProviderMenuOption {
+    NEW_PLAYLIST,
+}
+
+impl PlaylistProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = PlaylistProviderMenuOption>> {
+        Box::new([
+            PlaylistProviderMenuOption::NEW_PLAYLIST,
+        ].into_iter())
+    }
+}
+
+impl<'b> Display<'b> for PlaylistProvider {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(self.get_name()));
+
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.ids()
+                .map(|id| context.display_item(id))
+                .collect()
+            }
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
+impl Provider for PlaylistProvider {
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.selected
+    }
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.selected
+    }
+}
+impl CPProvider for PlaylistProvider {
+    fn add_provider(&mut self, id: ContentProviderID) {
+        self.providers.push(id);
+    }
+    fn providers<'a>(&'a self) -> Box<dyn Iterator<Item = &'a ContentProviderID> + 'a> {
+        Box::new(self.providers.iter())
+    }
+    fn providers_mut(&mut self) -> &mut Vec<ContentProviderID> {
+        &mut self.providers
+    }
+}
+
+impl Menu for PlaylistProvider {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, self_id: ContentProviderID) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        match option {
+            PlaylistProviderMenuOption::NEW_PLAYLIST => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    ContentManagerAction::AddCPToCPAndContentStack {
+                        id: self_id,
+                        cp: Playlist::new("new playlist").into(),
+                    },
+                ].into()
+            }
+        }
+    }
+}
+
+impl YankDest<ContentProviderID> for PlaylistProvider {
+    fn try_paste(&mut self, items: Vec<Yank<ContentProviderID>>, start_index: Option<usize>, self_id: ContentProviderID) -> YankAction {
+        try_paste_providers(items, start_index, self.providers.len(), self_id, convert_pasted)
+    }
+    fn dest_vec_mut(&mut self) -> Option<&mut Vec<ContentProviderID>> {
+        Some(&mut self.providers)
+    }
+}
+
+/// pasted providers are stored as they are if they are Playlists, other song providers are copied into a new Playlist
+fn convert_pasted(ctx: &mut YankContext, id: ContentProviderID) -> Option<ContentProviderID> {
+    let e = ctx.get_provider(id);
+    if e.as_any().downcast_ref::<Playlist>().is_some() {
+        ctx.register(id); // for being saved in PlaylistProvider
+        Some(id)
+    } else {
+        if e.as_song_provider().map(|cp| cp.songs().count()).unwrap_or(0) == 0 {
+            None
+        } else {
+            let mut songs = vec![];
+            let p = Playlist::from_provider(e, |id: SongID| {songs.push(id)}).into();
+            songs.into_iter().for_each(|id| ctx.register(id));
+            Some(ctx.alloc_provider(p))
+        }
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for PlaylistProvider {
+    impliment_content_provider!(PlaylistProvider, Provider, CPProvider, Menu, Display, CPYankDest);
+}
@@ -7,6 +7,10 @@ use crate::{
 };
 
 use std::borrow::Cow;
+use rand::{
+    Rng,
+    seq::SliceRandom,
+};
 use tui::{
     text::Span,
     style::{
@@ -18,16 +22,18 @@ use serde::{Serialize, Deserialize};
 
 use crate::{
     content::{
+        stack::StateContext,
         providers::{
             ContentProvider,
             traits::{
                 impliment_content_provider,
                 SongProvider,
                 Provider,
+                Menu,
                 ContentProviderTrait,
-                YankContext,
                 YankDest,
                 SongYankDest,
+                try_paste_songs,
             },
         },
         register::{
@@ -46,11 +52,13 @@ use crate::{
         display::{
             Display,
             ListBuilder,
+            SelectedText,
+            Item,
+            Line,
         },
     },
     service::editors::{
         Yank,
-        Edit,
         YankAction,
     },
 };
@@ -63,6 +71,53 @@ pub struct Queue {
     pub index: SelectedIndex,
     pub currently_playing: Option<usize>,
     pub source_cp: ContentProviderID, // weak
+    #[serde(default)]
+    pub play_mode: PlayMode,
+    /// indices into songs in the order they are played in PlayMode::Shuffle
+    #[serde(default)]
+    pub shuffle_order: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayMode {
+    Linear,
+    RepeatAll,
+    RepeatOne,
+    Shuffle,
+}
+impl Default for PlayMode {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+impl PlayMode {
+    pub fn iter() -> &'static [Self] {
+        &[
+            Self::Linear,
+            Self::RepeatAll,
+            Self::RepeatOne,
+            Self::Shuffle,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::RepeatAll => "repeat all",
+            Self::RepeatOne => "repeat one",
+            Self::Shuffle => "shuffle",
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QueueMenuOption {
+    LINEAR,
+    REPEAT_ALL,
+    REPEAT_ONE,
+    SHUFFLE,
+    RESHUFFLE,
 }
 impl Queue {
     /// panics is cp is not a SongProvider
@@ -88,6 +143,89 @@ impl Queue {
             index: Default::default(),
             currently_playing: None,
             source_cp: id, // this should not register. the cp might go down even if this persists
+            play_mode: Default::default(),
+            shuffle_order: Default::default(),
+        }
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = QueueMenuOption>> {
+        let play_mode = self.play_mode;
+        Box::new([
+            QueueMenuOption::LINEAR,
+            QueueMenuOption::REPEAT_ALL,
+            QueueMenuOption::REPEAT_ONE,
+            QueueMenuOption::SHUFFLE,
+            QueueMenuOption::RESHUFFLE,
+        ].into_iter()
+        .filter(move |o| match o {
+            QueueMenuOption::LINEAR => play_mode != PlayMode::Linear,
+            QueueMenuOption::REPEAT_ALL => play_mode != PlayMode::RepeatAll,
+            QueueMenuOption::REPEAT_ONE => play_mode != PlayMode::RepeatOne,
+            QueueMenuOption::SHUFFLE => play_mode != PlayMode::Shuffle,
+            QueueMenuOption::RESHUFFLE => play_mode == PlayMode::Shuffle,
+        }))
+    }
+
+    pub fn set_play_mode(&mut self, mode: PlayMode) {
+        self.play_mode = mode;
+        if mode == PlayMode::Shuffle {
+            self.reshuffle();
+        }
+    }
+
+    /// the currently playing song is kept at the start of the new order
+    pub fn reshuffle(&mut self) {
+        let mut order = (0..self.songs.len())
+        .filter(|&i| Some(i) != self.currently_playing)
+        .collect::<Vec<_>>();
+        order.shuffle(&mut rand::thread_rng());
+        self.shuffle_order = self.currently_playing
+        .filter(|&i| i < self.songs.len())
+        .into_iter()
+        .chain(order.into_iter())
+        .collect();
+    }
+
+    /// songs were inserted at these indices (indices after the insert). the rest keep their place in the order,
+    /// and the new ones are put at random places in the part of the order that is not played yet
+    fn shuffle_inserted(&mut self, inserted: &[usize]) {
+        if self.shuffle_order.is_empty() {
+            return;
+        }
+        let old_to_new = (0..self.songs.len())
+        .filter(|i| !inserted.contains(i))
+        .collect::<Vec<_>>();
+        self.shuffle_order.iter_mut().for_each(|i| *i = old_to_new[*i]);
+
+        let played = self.currently_playing
+        .and_then(|i| self.shuffle_order.iter().position(|&j| j == i))
+        .map(|pos| pos+1)
+        .unwrap_or(0);
+        let mut rng = rand::thread_rng();
+        for &i in inserted {
+            let pos = rng.gen_range(played..=self.shuffle_order.len());
+            self.shuffle_order.insert(pos, i);
+        }
+    }
+
+    /// songs at these indices (indices before the remove) were removed
+    fn shuffle_removed(&mut self, removed: &[usize]) {
+        if self.shuffle_order.is_empty() {
+            return;
+        }
+        self.shuffle_order = self.shuffle_order
+        .iter()
+        .filter(|i| !removed.contains(i))
+        .map(|&i| i - removed.iter().filter(|&&r| r < i).count())
+        .collect();
+    }
+
+    /// fallback in case the order went out of sync with the songs (eg. the db was edited)
+    fn check_shuffle_order(&mut self) {
+        let mut sorted = self.shuffle_order.clone();
+        sorted.sort();
+        if !sorted.into_iter().eq(0..self.songs.len()) {
+            self.reshuffle();
         }
     }
 
@@ -101,36 +239,69 @@ impl Queue {
         self.currently_playing = Some(index);
     }
 
-    pub fn next_song(&mut self) -> Option<SongID> {
-        self.currently_playing.as_mut().map(|i| {
-            if *i+1 < self.songs.len() {
-                *i = *i+1;
-                Some(self.songs[*i])
-            } else {
-                None
+    /// RepeatOne only repeats the song when it finishes on its own. skipping goes through the queue like RepeatAll
+    fn next_index(&mut self) -> Option<usize> {
+        let i = self.currently_playing?;
+        let len = self.songs.len();
+        match self.play_mode {
+            PlayMode::Linear => Some(i+1).filter(|&j| j < len),
+            PlayMode::RepeatAll | PlayMode::RepeatOne => Some(i+1).filter(|_| len > 0).map(|j| j % len),
+            PlayMode::Shuffle => {
+                self.check_shuffle_order();
+                let pos = self.shuffle_order.iter().position(|&j| j == i)?;
+                self.shuffle_order.get(pos+1).cloned()
             }
-        })
-        .flatten()
+        }
     }
 
-    pub fn prev_song(&mut self) -> Option<SongID> {
-        self.currently_playing.as_mut().map(|i| {
-            if *i > 0 {
-                *i = *i-1;
-                
-                self.songs.get(*i)
-                .map(|&id| id)
-            } else {
-                None
+    fn prev_index(&mut self) -> Option<usize> {
+        let i = self.currently_playing?;
+        let len = self.songs.len();
+        match self.play_mode {
+            PlayMode::Linear => i.checked_sub(1),
+            PlayMode::RepeatAll | PlayMode::RepeatOne => Some(i+len).filter(|_| len > 0).map(|j| (j-1) % len),
+            PlayMode::Shuffle => {
+                self.check_shuffle_order();
+                let pos = self.shuffle_order.iter().position(|&j| j == i)?;
+                pos.checked_sub(1).map(|p| self.shuffle_order[p])
             }
-        })
-        .flatten()
+        }
+    }
+
+    pub fn next_song(&mut self) -> Option<SongID> {
+        let i = self.next_index()?;
+        self.currently_playing = Some(i);
+        self.songs.get(i).cloned()
+    }
+
+    pub fn prev_song(&mut self) -> Option<SongID> {
+        let i = self.prev_index()?;
+        self.currently_playing = Some(i);
+        self.songs.get(i).cloned()
+    }
+
+    /// the song that finished_song would move to
+    pub fn peek_next(&mut self) -> Option<SongID> {
+        let i = match self.play_mode {
+            PlayMode::RepeatOne => self.currently_playing?,
+            _ => self.next_index()?,
+        };
+        self.songs.get(i).cloned()
+    }
+
+    /// the song to play after the current one ends on its own
+    pub fn finished_song(&mut self) -> Option<SongID> {
+        match self.play_mode {
+            PlayMode::RepeatOne => self.currently_playing.map(|i| self.songs.get(i).cloned()).flatten(),
+            _ => self.next_song(),
+        }
     }
 }
 
 impl SongProvider for Queue {
     fn add_song(&mut self, id: SongID) {
-        self.songs.push(id)
+        self.songs.push(id);
+        self.shuffle_inserted(&[self.songs.len()-1]);
     }
     fn songs<'a>(&'a self) -> Box<dyn Iterator<Item = &'a SongID> + 'a> {
         Box::new(self.songs.iter())
@@ -151,7 +322,9 @@ impl SongProvider for Queue {
                 if self.currently_playing.map(|i| item_index < i).unwrap_or(false) {
                     self.currently_playing = self.currently_playing.map(|i| i-1);
                 }
-                Some(self.songs_mut().remove(index))
+                let removed = self.songs_mut().remove(index);
+                self.shuffle_removed(&[index]);
+                Some(removed)
             }
             None => None,
         }
@@ -167,13 +340,32 @@ impl Provider for Queue {
     }
 }
 
+impl Menu for Queue {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, _: ContentProviderID) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        match option {
+            QueueMenuOption::LINEAR => self.set_play_mode(PlayMode::Linear),
+            QueueMenuOption::REPEAT_ALL => self.set_play_mode(PlayMode::RepeatAll),
+            QueueMenuOption::REPEAT_ONE => self.set_play_mode(PlayMode::RepeatOne),
+            QueueMenuOption::SHUFFLE => self.set_play_mode(PlayMode::Shuffle),
+            QueueMenuOption::RESHUFFLE => self.reshuffle(),
+        }
+        ContentManagerAction::PopContentStack
+    }
+}
+
 impl<'b> Display<'b> for Queue {
     type DisplayContext = DisplayContext<'b>;
     fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
         let mut lb = ListBuilder::default();
         let title = format!(
-            "Queue: {name}",
+            "Queue: {name} ({mode})",
             name = self.get_name(),
+            mode = self.play_mode.name(),
         );
         lb.title(Span::raw(title));
 
@@ -185,7 +377,21 @@ impl<'b> Display<'b> for Queue {
                 self.currently_playing.map(|i| items[i].text_style(Style::default().fg(Color::Rgb(200, 100, 0))));
                 items
             }
-            DisplayState::Menu(_) => unreachable!(),
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
             DisplayState::Edit(_) => unreachable!(),
         };
 
@@ -198,41 +404,7 @@ impl<'b> Display<'b> for Queue {
 
 impl YankDest<SongID> for Queue {
     fn try_paste(&mut self, items: Vec<Yank<SongID>>, start_index: Option<usize>, self_id: ContentProviderID) -> YankAction {
-        let num_items = self.songs.len();
-        vec![
-            YankAction::Callback {
-                callback: Box::new(move |mut ctx: YankContext| {
-                    items.iter().for_each(|y| {
-                        ctx.register(y.item); // for being stored in the Queue
-                        ctx.register(y.item); // for being stored in Edit
-                    });
-                    let yank = items.into_iter()
-                    .enumerate()
-                    .map(|(i, mut y)| {
-                        y.index = start_index.map(|j| j+i).unwrap_or(num_items + i);
-                        y
-                    })
-                    .collect::<Vec<_>>();
-                    ctx.register(self_id); // for being stored in Edit
-                    vec![
-                        YankAction::PasteIntoProvider {
-                            yank: yank.clone().into(),
-                            yanked_to: self_id,
-                            paste_pos: start_index,
-                        },
-                        YankAction::PushEdit {
-                            edit: Edit::Pasted {
-                                yank: yank.into(),
-                                yanked_to: self_id,
-                                paste_pos: start_index,
-                            },
-                        },
-                        YankAction::False,
-                        ContentManagerAction::RefreshDisplayContent.into(),
-                    ].into()
-                }),
-            },
-        ].into()
+        try_paste_songs(items, start_index, self.songs.len(), self_id)
     }
     fn dest_vec_mut(&mut self) -> Option<&mut Vec<SongID>> {
         Some(&mut self.songs)
@@ -252,10 +424,15 @@ impl YankDest<SongID> for Queue {
         
         let vecc = self.dest_vec_mut().unwrap();
         let len = vecc.len();
-        items.into_iter()
+        let inserted = items.into_iter()
         .enumerate()
         .map(|(i, y)| (start_index.map(|j| j+i).unwrap_or(len+i), y))
-        .for_each(|(i, y)| vecc.insert(i, y.item));
+        .map(|(i, y)| {
+            vecc.insert(i, y.item);
+            i
+        })
+        .collect::<Vec<_>>();
+        self.shuffle_inserted(&inserted);
     }
 
     /// each item will be at the associated index once the entire operation is done
@@ -267,6 +444,7 @@ impl YankDest<SongID> for Queue {
         let currently_playing = self.currently_playing;
         let vecc = self.dest_vec_mut().unwrap();
         items.sort_by(|a, b| a.index.cmp(&b.index));
+        let inserted = items.iter().map(|y| y.index).collect::<Vec<_>>();
         let mut items = items.into_iter().peekable();
         let mut old_items = std::mem::replace(vecc, vec![]).into_iter().peekable();
         while items.peek().is_some() || old_items.peek().is_some() {
@@ -289,6 +467,7 @@ impl YankDest<SongID> for Queue {
 
         self.currently_playing = self.currently_playing.map(|i| i+currently_playing_index_counter);
         self.currently_playing = self.currently_playing.map(|i| i.min(self.songs.len()-1));
+        self.shuffle_inserted(&inserted);
     }
 
     /// assuming T exists at the provided index (necessary for multiple of the same thing present in the list)
@@ -297,6 +476,7 @@ impl YankDest<SongID> for Queue {
         let mut currently_playing_index_counter = 0;
         let selected_index = Provider::get_selected_index(self).selected_index();
         let mut currently_playing = self.currently_playing;
+        let mut removed = vec![];
         let vecc = self.dest_vec_mut().unwrap();
         items.sort_by(|a, b| a.index.cmp(&b.index));
         let mut items = items.into_iter().peekable();
@@ -306,6 +486,7 @@ impl YankDest<SongID> for Queue {
             if let Some(y) = items.peek() {
                 if y.item == *id && y.index == i {
                     let _ = items.next();
+                    removed.push(i);
                     if i <= selected_index {
                         provider_index_counter += 1;
                     }
@@ -330,6 +511,7 @@ impl YankDest<SongID> for Queue {
                 i-currently_playing_index_counter
             }
         });
+        self.shuffle_removed(&removed);
         if items.peek().is_some() {
             dbg!(items.collect::<Vec<_>>());
             panic!("everything was not removed");
@@ -339,5 +521,5 @@ impl YankDest<SongID> for Queue {
 
 #[typetag::serde]
 impl ContentProviderTrait for Queue {
-    impliment_content_provider!(Queue, SongProvider, Provider, Display, SongYankDest);
+    impliment_content_provider!(Queue, SongProvider, Provider, Menu, Display, SongYankDest);
 }
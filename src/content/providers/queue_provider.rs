@@ -28,6 +28,7 @@ use crate::{
                 YankDest,
                 CPYankDest,
                 YankContext,
+                try_paste_providers,
             },
             queue::Queue,
         },
@@ -35,9 +36,6 @@ use crate::{
             DisplayContext,
             DisplayState,
         },
-        manager::{
-            action::ContentManagerAction,
-        },
     },
     app::{
         app::SelectedIndex,
@@ -47,7 +45,6 @@ use crate::{
         },
     },
     service::editors::{
-        Edit,
         YankAction,
         Yank,
     },
@@ -134,66 +131,31 @@ impl CPProvider for QueueProvider {
 
 impl YankDest<ContentProviderID> for QueueProvider {
     fn try_paste(&mut self, items: Vec<Yank<ContentProviderID>>, start_index: Option<usize>, self_id: ContentProviderID) -> YankAction {
-        let num_items = self.providers.len();
-        vec![
-            YankAction::Callback {
-                callback: Box::new(move |mut ctx: YankContext| {
-                    let items = items.into_iter()
-                    .filter_map(|y| {
-                        let id = y.item;
-                        let e = ctx.get_provider(id);
-                        if e.as_any().downcast_ref::<Queue>().is_some() {
-                            ctx.register(id); // for being saved in QueueProvider
-                            Some(id)
-                        } else {
-                            if e.as_song_provider().map(|cp| cp.songs().count()).unwrap_or(0) == 0 {
-                                None
-                            } else {
-                                let mut songs = vec![];
-                                let q = Queue::new(e, id, |id: SongID| {songs.push(id)}).into();
-                                songs.into_iter().for_each(|id| ctx.register(id));
-                                Some(ctx.alloc_provider(q))
-                            }
-                        }
-                    })
-                    .collect::<Vec<_>>();
-
-                    // for being stored in Edit
-                    items.iter().cloned().for_each(|id| ctx.register(id));
-                    ctx.register(self_id);
-
-                    let yank = items.into_iter()
-                    .enumerate()
-                    .map(|(i, id)| Yank {
-                        item: id,
-                        index: start_index.map(|j| j+i).unwrap_or(num_items + i),
-                    })
-                    .collect::<Vec<_>>();
-                    vec![
-                        YankAction::PasteIntoProvider {
-                            yank: yank.clone().into(),
-                            yanked_to: self_id,
-                            paste_pos: start_index,
-                        },
-                        YankAction::PushEdit {
-                            edit: Edit::Pasted {
-                                yank: yank.into(),
-                                yanked_to: self_id,
-                                paste_pos: start_index,
-                            },
-                        },
-                        YankAction::False, // cuz of custom PushEdit, we handle this here
-                        ContentManagerAction::RefreshDisplayContent.into(),
-                    ].into()
-                }),
-            },
-        ].into()
+        try_paste_providers(items, start_index, self.providers.len(), self_id, convert_pasted)
     }
     fn dest_vec_mut(&mut self) -> Option<&mut Vec<ContentProviderID>> {
         Some(&mut self.providers)
     }
 }
 
+/// pasted providers are stored as they are if they are Queues, other song providers are copied into a new Queue
+fn convert_pasted(ctx: &mut YankContext, id: ContentProviderID) -> Option<ContentProviderID> {
+    let e = ctx.get_provider(id);
+    if e.as_any().downcast_ref::<Queue>().is_some() {
+        ctx.register(id); // for being saved in QueueProvider
+        Some(id)
+    } else {
+        if e.as_song_provider().map(|cp| cp.songs().count()).unwrap_or(0) == 0 {
+            None
+        } else {
+            let mut songs = vec![];
+            let q = Queue::new(e, id, |id: SongID| {songs.push(id)}).into();
+            songs.into_iter().for_each(|id| ctx.register(id));
+            Some(ctx.alloc_provider(q))
+        }
+    }
+}
+
 #[typetag::serde]
 impl ContentProviderTrait for QueueProvider {
     impliment_content_provider!(QueueProvider, Provider, CPProvider, Display, CPYankDest);
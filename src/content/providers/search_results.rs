@@ -0,0 +1,204 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::borrow::Cow;
+use anyhow::Result;
+use tui::{
+    text::Span,
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        providers::{
+            traits::{
+                impliment_content_provider,
+                SongProvider,
+                CPProvider,
+                Provider,
+                ContentProviderTrait,
+            },
+        },
+        register::{
+            SongID,
+            ContentProviderID,
+        },
+        song::Song,
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            Display,
+            ListBuilder,
+        },
+    },
+    service::notifier::Severity,
+};
+
+/// the songs and providers from the whole register that match a query.
+/// it is only held by the content stack, so it goes away once it is popped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResults {
+    songs: Vec<SongID>,
+    providers: Vec<ContentProviderID>,
+    pub name: Cow<'static, str>,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    index: SelectedIndex,
+}
+impl SearchResults {
+    pub fn new(query: &str) -> Self {
+        Self {
+            songs: Default::default(),
+            providers: Default::default(),
+            name: format!("Search: {query}").into(),
+            index: Default::default(),
+        }
+    }
+}
+
+impl SongProvider for SearchResults {
+    fn add_song(&mut self, id: SongID) {
+        self.songs.push(id)
+    }
+    fn songs<'a>(&'a self) -> Box<dyn Iterator<Item = &'a SongID> + 'a> {
+        Box::new(self.songs.iter())
+    }
+    fn songs_mut(&mut self) -> &mut Vec<SongID> {
+        &mut self.songs
+    }
+}
+
+impl CPProvider for SearchResults {
+    fn add_provider(&mut self, id: ContentProviderID) {
+        self.providers.push(id);
+    }
+    fn providers<'a>(&'a self) -> Box<dyn Iterator<Item = &'a ContentProviderID> + 'a> {
+        Box::new(self.providers.iter())
+    }
+    fn providers_mut(&mut self) -> &mut Vec<ContentProviderID> {
+        &mut self.providers
+    }
+}
+
+impl Provider for SearchResults {
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.index
+    }
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.index
+    }
+}
+
+impl<'b> Display<'b> for SearchResults {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(self.get_name()));
+
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.ids()
+                .map(|id| context.display_item(id))
+                .collect()
+            }
+            DisplayState::Menu(_) => unreachable!(),
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for SearchResults {
+    impliment_content_provider!(SearchResults, SongProvider, CPProvider, Provider, Display);
+}
+
+
+pub fn search_action(query: String) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: LibrarySearch { query }.into(),
+    }
+}
+
+/// every word of the query has to be in the title, artist, album or path of a song (or the name of a provider).
+/// results whose title/name contains the whole query are shown first
+#[derive(Debug)]
+struct LibrarySearch {
+    query: String,
+}
+impl ContentManagerCallbackTrait for LibrarySearch {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let query = self.query.trim().to_lowercase();
+        let terms = query.split_whitespace().collect::<Vec<_>>();
+        if terms.is_empty() {
+            return Ok(());
+        }
+        let matches = |text: &str| {
+            let text = text.to_lowercase();
+            terms.iter().all(|t| text.contains(t))
+        };
+        let rank = |name: &str| !name.to_lowercase().contains(&query);
+
+        let mut songs = ch.songs
+        .ids()
+        .filter(|&id| matches(&song_search_text(ch.get_song(id))))
+        .collect::<Vec<_>>();
+        songs.sort_by_key(|&id| rank(ch.get_song(id).as_display().title()));
+
+        let main_provider = ch.content_stack.main_provider();
+        let mut providers = ch.content_providers
+        .ids()
+        .filter(|&id| id != main_provider)
+        .filter(|&id| !ch.get_provider(id).as_any().is::<SearchResults>())
+        .filter(|&id| matches(&ch.get_provider(id).as_display().get_name()))
+        .collect::<Vec<_>>();
+        providers.sort_by_key(|&id| rank(&ch.get_provider(id).as_display().get_name()));
+
+        if songs.is_empty() && providers.is_empty() {
+            ch.notify(Severity::Info, format!("nothing found for '{}'", self.query.trim()));
+            return Ok(());
+        }
+
+        let mut results = SearchResults::new(self.query.trim());
+        for id in songs {
+            ch.register(id); // for being stored in SearchResults
+            results.add_song(id);
+        }
+        for id in providers {
+            ch.register(id); // for being stored in SearchResults
+            results.add_provider(id);
+        }
+        let id = ch.alloc_content_provider(results.into());
+        ContentManagerAction::PushToContentStack { id: id.into() }.apply(ch)?;
+        ch.unregister(id); // only the content stack holds it
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+fn song_search_text(song: &Song) -> String {
+    let path = song.file_path();
+    let song = song.as_display();
+    [Some(song.title()), song.artist(), song.album(), path]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
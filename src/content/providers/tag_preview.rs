@@ -0,0 +1,228 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::borrow::Cow;
+use anyhow::Result;
+use tui::{
+    text::Span,
+    style::{
+        Color,
+        Style,
+    },
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    content::{
+        providers::traits::{
+            impliment_content_provider,
+            ContentProviderTrait,
+            SongProvider,
+            Provider,
+            Menu,
+        },
+        register::{
+            SongID,
+            ContentProviderID,
+        },
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+        display::{
+            DisplayContext,
+            DisplayState,
+        },
+        stack::StateContext,
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            Display,
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+    service::{
+        tag_editor::{
+            self,
+            TagChange,
+        },
+        editors::{
+            Edit,
+            YankAction,
+        },
+        notifier::Severity,
+    },
+};
+
+/// what a batch tag edit would change. nothing is written to the files till it is applied from the menu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagPreview {
+    songs: Vec<SongID>,
+    changes: Vec<TagChange>,
+    name: Cow<'static, str>,
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    index: SelectedIndex,
+}
+
+impl TagPreview {
+    pub fn new(changes: Vec<TagChange>) -> Self {
+        Self {
+            songs: changes.iter().map(|c| c.song).collect(),
+            name: format!("Tag edit preview: {} songs", changes.len()).into(),
+            changes,
+            index: Default::default(),
+        }
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = TagPreviewMenuOption>> {
+        Box::new([
+            TagPreviewMenuOption::APPLY,
+            TagPreviewMenuOption::CANCEL,
+        ].into_iter())
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TagPreviewMenuOption {
+    APPLY,
+    CANCEL,
+}
+
+impl SongProvider for TagPreview {
+    fn add_song(&mut self, id: SongID) {
+        self.songs.push(id)
+    }
+    fn songs<'a>(&'a self) -> Box<dyn Iterator<Item = &'a SongID> + 'a> {
+        Box::new(self.songs.iter())
+    }
+    fn songs_mut(&mut self) -> &mut Vec<SongID> {
+        &mut self.songs
+    }
+}
+
+impl Provider for TagPreview {
+    fn get_selected_index(&self) -> &SelectedIndex {
+        &self.index
+    }
+    fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.index
+    }
+}
+
+impl Menu for TagPreview {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, self_id: ContentProviderID) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        match option {
+            TagPreviewMenuOption::APPLY => {
+                vec![
+                    ContentManagerAction::PopContentStack, // menu
+                    ContentManagerAction::Callback {
+                        callback: ApplyTagEdit { id: self_id }.into(),
+                    },
+                ].into()
+            }
+            TagPreviewMenuOption::CANCEL => {
+                vec![
+                    ContentManagerAction::PopContentStack, // menu
+                    ContentManagerAction::PopContentStack, // preview
+                ].into()
+            }
+        }
+    }
+}
+
+impl<'b> Display<'b> for TagPreview {
+    type DisplayContext = DisplayContext<'b>;
+    fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(self.get_name()));
+
+        let show = |v: &Option<String>| v.as_ref().map(|v| format!("'{v}'")).unwrap_or("(none)".into());
+        lb.items = match context.state {
+            DisplayState::Normal => {
+                self.changes
+                .iter()
+                .map(|c| {
+                    let title = context.songs.get(c.song).unwrap().as_display().title().to_owned();
+                    format!("{title}: {field} {from} -> {to}", field = c.field.name(), from = show(&c.from), to = show(&c.to))
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
+            DisplayState::Edit(_) => unreachable!(),
+        };
+
+        lb
+    }
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
+#[typetag::serde]
+impl ContentProviderTrait for TagPreview {
+    impliment_content_provider!(TagPreview, SongProvider, Provider, Menu, Display);
+}
+
+/// writes the tags and adds the changes that worked to the edit history, so they can be undone
+#[derive(Debug)]
+struct ApplyTagEdit {
+    id: ContentProviderID,
+}
+impl ContentManagerCallbackTrait for ApplyTagEdit {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let changes = ch.get_provider(self.id)
+        .as_any()
+        .downcast_ref::<TagPreview>()
+        .unwrap()
+        .changes
+        .clone();
+        let done = tag_editor::write_changes(ch, &changes, false);
+        if !done.is_empty() {
+            ch.notify(Severity::Info, format!("edited the tags of {} songs", done.len()));
+            done.iter().for_each(|c| ch.register(c.song)); // for being stored in the EditManager
+            YankAction::ClearUndoStack.apply(ch)?;
+            ch.edit_manager.edit_stack.push(Edit::TagEdit { changes: done });
+            ch.request_save();
+        }
+        if ch.content_stack.last() == self.id.into() {
+            ContentManagerAction::PopContentStack.apply(ch)?;
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
@@ -37,6 +37,8 @@ use crate::{
     service::editors::{
         YankAction,
         Yank,
+        YankedContent,
+        Edit,
     },
 };
 
@@ -318,6 +320,75 @@ pub trait YankDest<T: PartialEq + Copy + Debug>: ContentProviderTrait {
     }
 }
 
+/// YankDest::try_paste for song lists that just hold the pasted songs
+pub fn try_paste_songs(items: Vec<Yank<SongID>>, start_index: Option<usize>, num_items: usize, self_id: ContentProviderID) -> YankAction {
+    vec![
+        YankAction::Callback {
+            callback: Box::new(move |mut ctx: YankContext| {
+                items.iter().for_each(|y| ctx.register(y.item)); // for being stored in the provider
+                let items = items.into_iter().map(|y| y.item).collect();
+                paste_and_push_edit(ctx, items, start_index, num_items, self_id)
+            }),
+        },
+    ].into()
+}
+
+/// YankDest::try_paste for provider lists. convert gives the provider that is stored in the list in place of the pasted one
+/// (already registered for being stored there), or None if it can't be stored there
+pub fn try_paste_providers(
+    items: Vec<Yank<ContentProviderID>>,
+    start_index: Option<usize>,
+    num_items: usize,
+    self_id: ContentProviderID,
+    convert: fn(&mut YankContext, ContentProviderID) -> Option<ContentProviderID>,
+) -> YankAction {
+    vec![
+        YankAction::Callback {
+            callback: Box::new(move |mut ctx: YankContext| {
+                let items = items.into_iter()
+                .filter_map(|y| convert(&mut ctx, y.item))
+                .collect();
+                paste_and_push_edit(ctx, items, start_index, num_items, self_id)
+            }),
+        },
+    ].into()
+}
+
+/// the items should already be registered for being stored in the provider. they (and the provider) are registered again for the Edit
+fn paste_and_push_edit<T>(mut ctx: YankContext, items: Vec<T>, start_index: Option<usize>, num_items: usize, self_id: ContentProviderID) -> YankAction
+    where
+        T: Copy + Into<GlobalContent>,
+        Vec<Yank<T>>: Into<YankedContent>,
+{
+    // for being stored in Edit
+    items.iter().for_each(|&id| ctx.register(id));
+    ctx.register(self_id);
+
+    let yank = items.into_iter()
+    .enumerate()
+    .map(|(i, item)| Yank {
+        item,
+        index: start_index.map(|j| j+i).unwrap_or(num_items + i),
+    })
+    .collect::<Vec<_>>();
+    vec![
+        YankAction::PasteIntoProvider {
+            yank: yank.clone().into(),
+            yanked_to: self_id,
+            paste_pos: start_index,
+        },
+        YankAction::PushEdit {
+            edit: Edit::Pasted {
+                yank: yank.into(),
+                yanked_to: self_id,
+                paste_pos: start_index,
+            },
+        },
+        YankAction::False, // cuz of custom PushEdit, we handle this here
+        ContentManagerAction::RefreshDisplayContent.into(),
+    ].into()
+}
+
 pub trait SongYankDest: YankDest<SongID> {}
 impl<T: YankDest<SongID>> SongYankDest for T {}
 pub trait CPYankDest: YankDest<ContentProviderID> {}
@@ -20,7 +20,11 @@ use serde::{Serialize, Deserialize};
 use crate::{
     content::{
         stack::StateContext,
-        manager::action::ContentManagerAction,
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
         register::{
             SongID,
             ContentProviderID,
@@ -45,6 +49,7 @@ use crate::{
     },
     app::{
         app::SelectedIndex,
+        action::TypingCallback,
         display::{
             Display,
             ListBuilder,
@@ -431,7 +436,7 @@ impl Editable for YTExplorer {
                             ContentManagerAction::EnableTyping {
                                 content: self.search_term.clone(),
                                 loader: self_id.into(),
-                                callback: Box::new(move |me: &mut ContentProvider, content: String| {
+                                callback: TypingCallback::ContentProvider(Box::new(move |me: &mut ContentProvider, content: String| {
                                     let cp = me.as_any_mut().downcast_mut::<Self>().unwrap();
                                     cp.loaded = true;
                                     cp.name = Cow::from(format!("Youtube: {content}"));
@@ -446,7 +451,7 @@ impl Editable for YTExplorer {
                                         ContentManagerAction::MaybePushToContentStack {id: self_id.into()},
                                         cp.get_search_action(self_id),
                                     ].into()
-                                }),
+                                })),
                             },
                         ].into()
                     }
@@ -474,7 +479,7 @@ impl Editable for YTExplorer {
                 ContentManagerAction::EnableTyping {
                     content: "".into(),
                     loader: self_id.into(),
-                    callback: Box::new(move |me: &mut ContentProvider, content: String| {
+                    callback: TypingCallback::ContentProvider(Box::new(move |me: &mut ContentProvider, content: String| {
                         let cp = me.as_any_mut().downcast_mut::<Self>().unwrap();
                         cp.loaded = true;
                         cp.name = Cow::from(format!("Youtube: {e:#?} Url"));
@@ -487,7 +492,7 @@ impl Editable for YTExplorer {
                             ContentManagerAction::MaybePushToContentStack {id: self_id.into()},
                             cp.get_url_action(self_id, e, content),
                         ].into()
-                    }),
+                    })),
                 }
             }
         }
@@ -512,6 +517,45 @@ impl ContentProviderTrait for YTExplorer {
     impliment_content_provider!(YTExplorer, Provider, Loadable, Editable, SongProvider, CPProvider, Display);
 }
 
+/// adds a new YTExplorer to main and searches the query in it
+pub fn search_action(query: String, filter: YTSearchFilter) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: YTSearch { query, filter }.into(),
+    }
+}
+
+#[derive(Debug)]
+struct YTSearch {
+    query: String,
+    filter: YTSearchFilter,
+}
+impl ContentManagerCallbackTrait for YTSearch {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let yte = YTExplorer {
+            name: Cow::from(format!("Youtube: {}", self.query)),
+            search_term: self.query,
+            filter: self.filter,
+            loaded: true,
+            ..Default::default()
+        };
+        let id = ch.alloc_content_provider(yte.into());
+        let mp = ch.content_stack.main_provider();
+        ch.get_provider_mut(mp)
+        .as_provider_mut()
+        .unwrap()
+        .add_provider(id);
+        ContentManagerAction::PushToContentStack { id: id.into() }.apply(ch)?;
+        let action = ch.get_provider(id)
+        .as_any()
+        .downcast_ref::<YTExplorer>()
+        .unwrap()
+        .get_search_action(id);
+        action.apply(ch)?;
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 enum Editables {
     Main(YTEEditables),
@@ -542,7 +586,7 @@ pub enum YTSearchFilter {
     Playlists,
 }
 impl YTSearchFilter {
-    fn iter() -> &'static [Self] {
+    pub fn iter() -> &'static [Self] {
         &[
             Self::Albums,
             Self::Songs,
@@ -550,7 +594,10 @@ impl YTSearchFilter {
             Self::Playlists,
         ]
     }
-    fn ytmusic_filter(&self) -> &'static str {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter().iter().cloned().find(|f| f.ytmusic_filter() == name)
+    }
+    pub fn ytmusic_filter(&self) -> &'static str {
         // https://ytmusicapi.readthedocs.io/en/latest/reference.html#ytmusicapi.YTMusic.search
         match self {
             Self::Albums => "albums",
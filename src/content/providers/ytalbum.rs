@@ -10,6 +10,10 @@ use anyhow::Result;
 use std::borrow::Cow;
 use tui::{
     text::Span,
+    style::{
+        Color,
+        Style,
+    },
 };
 use serde::{Serialize, Deserialize};
 
@@ -17,11 +21,13 @@ use crate::{
     content::{
         manager::action::ContentManagerAction,
         providers::{
+            downloads,
             traits::{
                 impliment_content_provider,
                 SongProvider,
                 Provider,
                 Loadable,
+                Menu,
                 ContentProviderTrait,
             },
         },
@@ -33,12 +39,16 @@ use crate::{
             DisplayContext,
             DisplayState,
         },
+        stack::StateContext,
     },
     app::{
         app::SelectedIndex,
         display::{
             Display,
             ListBuilder,
+            SelectedText,
+            Item,
+            Line,
         },
     },
     service::{
@@ -88,6 +98,20 @@ impl YTAlbum {
     }
 }
 
+impl YTAlbum {
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = YTAlbumMenuOption>> {
+        Box::new([
+            YTAlbumMenuOption::DOWNLOAD,
+        ].into_iter())
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum YTAlbumMenuOption {
+    DOWNLOAD,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 enum YTAlbumID {
     PlaylistID(Cow<'static, str>),
@@ -210,6 +234,24 @@ impl Provider for YTAlbum {
     }
 }
 
+impl Menu for YTAlbum {
+    fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    fn apply_option(&mut self, ctx: &mut StateContext, _: ContentProviderID) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        match option {
+            YTAlbumMenuOption::DOWNLOAD => {
+                vec![
+                    ContentManagerAction::PopContentStack,
+                    downloads::download_action(self.songs.clone()),
+                ].into()
+            }
+        }
+    }
+}
+
 impl<'b> Display<'b> for YTAlbum {
     type DisplayContext = DisplayContext<'b>;
     fn display(&self, context: Self::DisplayContext) -> ListBuilder<'static> {
@@ -222,7 +264,21 @@ impl<'b> Display<'b> for YTAlbum {
                 .map(|id| context.display_item(id))
                 .collect()
             }
-            DisplayState::Menu(_) => unreachable!(),
+            DisplayState::Menu(ctx) => {
+                self.menu(ctx)
+                .map(|o| {
+                    format!("{o:#?}")
+                    .replace("_", " ")
+                    .to_lowercase()
+                })
+                .map(Span::from)
+                .map(Line::new)
+                .map(|line| Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+                })
+                .collect()
+            }
             DisplayState::Edit(_) => unreachable!(),
         };
 
@@ -235,5 +291,5 @@ impl<'b> Display<'b> for YTAlbum {
 
 #[typetag::serde]
 impl ContentProviderTrait for YTAlbum {
-    impliment_content_provider!(YTAlbum, SongProvider, Loadable, Provider, Display);
+    impliment_content_provider!(YTAlbum, SongProvider, Loadable, Provider, Menu, Display);
 }
@@ -263,6 +263,13 @@ impl<T, P> ContentRegister<T, P>
     pub fn len(&self) -> usize {
         self.items.len()
     }
+
+    /// ids of all the items currently alive in the register
+    pub fn ids<'a>(&'a self) -> impl Iterator<Item = P> + 'a {
+        (0..self.items.len())
+        .filter_map(|i| self.get_id_count(i))
+        .map(|(id, _)| P::from(id))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
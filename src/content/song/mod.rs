@@ -12,6 +12,8 @@ pub mod untagged_file_song;
 pub mod yt_song;
 
 use traits::SongTrait;
+use tagged_file_song::TaggedFileSong;
+use untagged_file_song::UntaggedFileSong;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Song(Box<dyn SongTrait>);
@@ -19,6 +21,14 @@ impl Song {
     pub fn new(s: Box<dyn SongTrait>) -> Self {
         Self(s)
     }
+
+    /// None if the song is not a local file
+    pub fn file_path(&self) -> Option<&str> {
+        let any = self.as_any();
+        any.downcast_ref::<TaggedFileSong>()
+        .map(|s| s.path())
+        .or(any.downcast_ref::<UntaggedFileSong>().map(|s| s.path()))
+    }
 }
 
 impl Deref for Song {
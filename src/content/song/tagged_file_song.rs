@@ -14,11 +14,20 @@ use std::{
 use anyhow::{
     Result,
     Context,
+    bail,
 };
 use lofty::{
     TaggedFile,
     ItemKey,
     AudioFile,
+    Tag,
+};
+use tui::{
+    text::Span,
+    style::{
+        Color,
+        Style,
+    },
 };
 use serde::{Deserialize, Serialize};
 
@@ -31,11 +40,31 @@ use crate::{
                 RustParallelAction,
             },
         },
-        song::traits::{
-            SongTrait,
-            Func,
-            SongDisplay,
+        song::{
+            Song,
+            traits::{
+                SongTrait,
+                Func,
+                SongDisplay,
+                SongEditable,
+            },
         },
+        register::SongID,
+        stack::StateContext,
+    },
+    app::{
+        app::SelectedIndex,
+        action::TypingCallback,
+        display::{
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+    service::notifier::{
+        Notification,
+        Severity,
     },
 };
 
@@ -45,6 +74,12 @@ pub struct TaggedFileSong {
     album: Option<String>,
     artist: Option<String>,
     path: Cow<'static, str>,
+    #[serde(default)]
+    track: Option<String>,
+    #[serde(default)]
+    year: Option<String>,
+    #[serde(default)]
+    genre: Option<String>,
 }
 impl TaggedFileSong {
     pub fn from_file_path<'a>(path: Cow<'a, str>) -> Result<Option<Self>> {
@@ -61,6 +96,9 @@ impl TaggedFileSong {
                 title: title.unwrap().to_owned(),
                 album: album.map(String::from),
                 artist: artist.map(String::from),
+                track: st.track().map(String::from),
+                year: st.year().map(String::from),
+                genre: st.genre().map(String::from),
             };
             Ok(Some(song))
         } else {
@@ -68,6 +106,68 @@ impl TaggedFileSong {
         }
     }
 
+    pub fn new(path: String, title: String, album: Option<String>, artist: Option<String>) -> Self {
+        Self {
+            title,
+            album,
+            artist,
+            path: path.into(),
+            track: None,
+            year: None,
+            genre: None,
+        }
+    }
+
+    /// None if there is no title
+    pub fn from_tags(path: String, tag: impl Fn(TagField) -> Option<String>) -> Option<Self> {
+        let song = Self {
+            title: tag(TagField::Title)?,
+            album: tag(TagField::Album),
+            artist: tag(TagField::Artist),
+            path: path.into(),
+            track: tag(TagField::Track),
+            year: tag(TagField::Year),
+            genre: tag(TagField::Genre),
+        };
+        Some(song)
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_ref()
+    }
+
+    pub fn get_tag(&self, field: TagField) -> Option<&str> {
+        match field {
+            TagField::Title => Some(self.title.as_str()),
+            TagField::Artist => self.artist.as_deref(),
+            TagField::Album => self.album.as_deref(),
+            TagField::Track => self.track.as_deref(),
+            TagField::Year => self.year.as_deref(),
+            TagField::Genre => self.genre.as_deref(),
+        }
+    }
+
+    /// writes the tag to the file first, the song is only changed if that works.
+    /// None removes the tag (the title cannot be removed)
+    pub fn set_tag(&mut self, field: TagField, value: Option<String>) -> Result<()> {
+        let value = value.map(|v| v.trim().to_owned()).filter(|v| !v.is_empty());
+        field.validate(value.as_deref())?;
+        write_tag(self.path(), field, value.as_deref())?;
+        match field {
+            TagField::Title => self.title = value.unwrap(),
+            TagField::Artist => self.artist = value,
+            TagField::Album => self.album = value,
+            TagField::Track => self.track = value,
+            TagField::Year => self.year = value,
+            TagField::Genre => self.genre = value,
+        }
+        Ok(())
+    }
+
+    fn editables(&self, _: &StateContext) -> Box<dyn Iterator<Item = TagField>> {
+        Box::new(TagField::iter().iter().cloned())
+    }
+
     pub fn show_art_action(path: Cow<'static, str>) -> ContentManagerAction {
         vec![
             ContentManagerAction::ClearImage,
@@ -97,6 +197,70 @@ impl TaggedFileSong {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TagField {
+    Title,
+    Artist,
+    Album,
+    Track,
+    Year,
+    Genre,
+}
+impl TagField {
+    pub fn iter() -> &'static [Self] {
+        &[Self::Title, Self::Artist, Self::Album, Self::Track, Self::Year, Self::Genre]
+    }
+
+    /// "title", "artist", ...
+    pub fn name(&self) -> String {
+        format!("{self:?}").to_lowercase()
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter().iter().cloned().find(|f| f.name() == name.to_lowercase())
+    }
+
+    fn item_key(&self) -> ItemKey {
+        match self {
+            Self::Title => ItemKey::TrackTitle,
+            Self::Artist => ItemKey::TrackArtist,
+            Self::Album => ItemKey::AlbumTitle,
+            Self::Track => ItemKey::TrackNumber,
+            Self::Year => ItemKey::Year,
+            Self::Genre => ItemKey::Genre,
+        }
+    }
+
+    pub fn validate(&self, value: Option<&str>) -> Result<()> {
+        match (self, value) {
+            (Self::Title, None) => bail!("title cannot be empty"),
+            (Self::Track | Self::Year, Some(v)) => {
+                v.parse::<u32>().with_context(|| format!("{self:?} has to be a number, got '{v}'"))?;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// sets (or removes) the value in the primary tag of the file. a tag is created if the file has none
+pub fn write_tag(path: &str, field: TagField, value: Option<&str>) -> Result<()> {
+    let mut tf = lofty::read_from_path(path, false)?;
+    if tf.primary_tag().is_none() {
+        let tag_type = tf.primary_tag_type();
+        tf.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tf.primary_tag_mut().unwrap();
+    match value {
+        Some(v) => {
+            tag.insert_text(field.item_key(), v.to_owned());
+        }
+        None => tag.remove_key(&field.item_key()),
+    }
+    tag.save_to_path(path)?;
+    Ok(())
+}
+
 struct TaggedSong(TaggedFile);
 impl From<TaggedFile> for TaggedSong {
     fn from(f: TaggedFile) -> Self {
@@ -113,6 +277,15 @@ impl TaggedSong {
     fn album(&self) -> Option<&str> {
         self.get_val(&ItemKey::AlbumTitle)
     }
+    fn track(&self) -> Option<&str> {
+        self.get_val(&ItemKey::TrackNumber)
+    }
+    fn year(&self) -> Option<&str> {
+        self.get_val(&ItemKey::Year)
+    }
+    fn genre(&self) -> Option<&str> {
+        self.get_val(&ItemKey::Genre)
+    }
     fn get_val(&self, key: &ItemKey) -> Option<&str> {
         self.0
         .tags()
@@ -165,9 +338,9 @@ impl SongTrait for TaggedFileSong {
     fn get_uri(&self, callback: Func) -> Result<ContentManagerAction> {
         callback(format!("file://{}", self.path))
     }
-    fn play(&self) -> Result<ContentManagerAction> {
-        self.get_uri(Box::new(|uri: String| {
-            Ok(ContentManagerAction::PlaySongURI { uri })
+    fn play(&self, id: SongID) -> Result<ContentManagerAction> {
+        self.get_uri(Box::new(move |uri: String| {
+            Ok(ContentManagerAction::PlaySongURI { id, uri })
         }))
     }
     fn show_art(&self) -> Result<ContentManagerAction> {
@@ -178,6 +351,15 @@ impl SongTrait for TaggedFileSong {
     fn as_display(&self) -> &dyn super::traits::SongDisplay {
         self
     }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn as_editable(&self) -> Option<&dyn SongEditable> {
+        Some(self)
+    }
+    fn as_editable_mut(&mut self) -> Option<&mut dyn SongEditable> {
+        Some(self)
+    }
 }
 
 impl SongDisplay for TaggedFileSong {
@@ -191,3 +373,60 @@ impl SongDisplay for TaggedFileSong {
         self.artist.as_ref().map(String::as_str)
     }
 }
+
+impl SongEditable for TaggedFileSong {
+    fn select_editable(&mut self, ctx: &mut StateContext, self_id: SongID) -> ContentManagerAction {
+        let i = ctx.last().selected_index();
+        let field = self.editables(ctx).skip(i).next().unwrap();
+        let mut index = SelectedIndex::default();
+        index.select(i);
+        ctx.push(index);
+        ContentManagerAction::EnableTyping {
+            content: self.get_tag(field).unwrap_or_default().to_owned(),
+            loader: self_id.into(),
+            callback: TypingCallback::Song(Box::new(move |me: &mut Song, content: String| {
+                // the song might have changed while typing (eg. a library rescan), so the current one is edited
+                let song = me.as_any().downcast_ref::<Self>().cloned();
+                let action = match song {
+                    Some(mut song) => {
+                        match song.set_tag(field, Some(content)) {
+                            Ok(()) => {
+                                *me = song.into();
+                                ContentManagerAction::RequestSave
+                            }
+                            Err(err) => {
+                                let message = format!("could not edit the tags of '{}': {err}", song.path());
+                                ContentManagerAction::Notify { notif: Notification::new(Severity::Error, message) }
+                            }
+                        }
+                    }
+                    None => {
+                        let message = format!("'{}' changed while it was being edited", me.as_display().title());
+                        ContentManagerAction::Notify { notif: Notification::new(Severity::Error, message) }
+                    }
+                };
+                vec![
+                    ContentManagerAction::PopContentStack, // typing
+                    action,
+                ].into()
+            })),
+        }
+    }
+    fn num_editables(&self, ctx: &StateContext) -> usize {
+        self.editables(ctx).count()
+    }
+    fn display_editables(&self, ctx: &StateContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(format!("Edit: {}", self.title)));
+        lb.items = self.editables(ctx)
+        .map(|e| format!("{e:#?}: {value}", value = self.get_tag(e).unwrap_or_default()))
+        .map(Span::from)
+        .map(Line::new)
+        .map(|line| Item {
+            text: vec![line],
+            selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+        })
+        .collect();
+        lb
+    }
+}
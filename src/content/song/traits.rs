@@ -13,6 +13,7 @@ use std::{
 };
 use anyhow::{
     Result,
+    anyhow,
 };
 use typetag;
 
@@ -24,7 +25,11 @@ use crate::{
             },
         },
         song::Song,
+        register::SongID,
+        providers::downloads::JobID,
+        stack::StateContext,
     },
+    app::display::ListBuilder,
 };
 
 
@@ -58,7 +63,8 @@ where T: SongTrait + 'static
 
 #[typetag::serde(tag = "type")]
 pub trait SongTrait: Send + Sync + Debug + SongClone {
-    fn play(&self) -> Result<ContentManagerAction>;
+    /// the action should play the uri as this id, so that it can be dropped if another song was played in the meantime
+    fn play(&self, id: SongID) -> Result<ContentManagerAction>;
     // song might have to get the uri from the interwebs, so cant directly retrun a string
     fn get_uri(&self, callback: Func) -> Result<ContentManagerAction>;
 
@@ -68,8 +74,9 @@ pub trait SongTrait: Send + Sync + Debug + SongClone {
     fn show_art(&self) -> Result<ContentManagerAction>;
 
     fn is_online(&self) -> bool;
-    fn save_to_path(&self, _: &str) {
-        unreachable!()
+    /// downloads the song into config().music_path as the job. the downloaded song is sent to downloads::finished_action
+    fn save_to_path(&self, _job: JobID) -> Result<ContentManagerAction> {
+        Err(anyhow!("song cannot be downloaded"))
     }
 
     fn get_all_info(&self) -> Box<dyn Iterator<Item = Cow<'static, str>>> {
@@ -78,6 +85,14 @@ pub trait SongTrait: Send + Sync + Debug + SongClone {
     fn get_showable_info(&self) -> Box<dyn Iterator<Item = Cow<'static, str>>>;
 
     fn as_display(&self) -> &dyn SongDisplay;
+    fn as_any(&self) -> &dyn std::any::Any;
+
+    fn as_editable(&self) -> Option<&dyn SongEditable> {
+        None
+    }
+    fn as_editable_mut(&mut self) -> Option<&mut dyn SongEditable> {
+        None
+    }
 }
 
 pub trait Playable {
@@ -102,6 +117,13 @@ pub trait Showable {
 
 }
 
+/// like providers::traits::Editable, but for songs
+pub trait SongEditable {
+    fn select_editable(&mut self, ctx: &mut StateContext, self_id: SongID) -> ContentManagerAction;
+    fn num_editables(&self, ctx: &StateContext) -> usize;
+    fn display_editables(&self, ctx: &StateContext) -> ListBuilder<'static>;
+}
+
 pub trait SongDisplay { // nothing is gonna be 'static str here, so not bothering to do Cow s here
     fn title(&self) -> &str;
     fn artist(&self) -> Option<&str> {
@@ -14,6 +14,13 @@ use std::{
 use anyhow::{
     Result,
 };
+use tui::{
+    text::Span,
+    style::{
+        Color,
+        Style,
+    },
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -24,13 +31,34 @@ use crate::{
             },
         },
         song::{
-            tagged_file_song::TaggedFileSong,
+            Song,
+            tagged_file_song::{
+                TaggedFileSong,
+                TagField,
+            },
             traits::{
                 SongTrait,
                 Func,
                 SongDisplay,
+                SongEditable,
             },
         },
+        register::SongID,
+        stack::StateContext,
+    },
+    app::{
+        app::SelectedIndex,
+        action::TypingCallback,
+        display::{
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+    service::notifier::{
+        Notification,
+        Severity,
     },
 };
 
@@ -47,6 +75,10 @@ impl UntaggedFileSong {
         };
         song
     }
+
+    pub fn path(&self) -> &str {
+        self.path.as_ref()
+    }
 }
 
 #[typetag::serde]
@@ -62,9 +94,9 @@ impl SongTrait for UntaggedFileSong {
     fn get_uri(&self, callback: Func) -> Result<ContentManagerAction> {
         callback(format!("file://{}", self.path))
     }
-    fn play(&self) -> Result<ContentManagerAction> {
-        self.get_uri(Box::new(|uri: String| {
-            Ok(ContentManagerAction::PlaySongURI { uri })
+    fn play(&self, id: SongID) -> Result<ContentManagerAction> {
+        self.get_uri(Box::new(move |uri: String| {
+            Ok(ContentManagerAction::PlaySongURI { id, uri })
         }))
     }
     fn show_art(&self) -> Result<ContentManagerAction> {
@@ -74,6 +106,15 @@ impl SongTrait for UntaggedFileSong {
     fn as_display(&self) -> &dyn super::traits::SongDisplay {
         self
     }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn as_editable(&self) -> Option<&dyn SongEditable> {
+        Some(self)
+    }
+    fn as_editable_mut(&mut self) -> Option<&mut dyn SongEditable> {
+        Some(self)
+    }
 }
 
 impl SongDisplay for UntaggedFileSong {
@@ -81,3 +122,64 @@ impl SongDisplay for UntaggedFileSong {
         self.title.as_ref()
     }
 }
+
+/// only the title can be set here. once it is, the song becomes a TaggedFileSong and the rest of the tags can be edited
+impl SongEditable for UntaggedFileSong {
+    fn select_editable(&mut self, ctx: &mut StateContext, self_id: SongID) -> ContentManagerAction {
+        let mut index = SelectedIndex::default();
+        index.select(ctx.last().selected_index());
+        ctx.push(index);
+        ContentManagerAction::EnableTyping {
+            content: self.title.clone(),
+            loader: self_id.into(),
+            callback: TypingCallback::Song(Box::new(move |me: &mut Song, content: String| {
+                // the song might have changed while typing (eg. a library rescan might have found its tags)
+                let song = match me.as_any().downcast_ref::<Self>() {
+                    Some(s) => Some(TaggedFileSong::new(s.path.clone().into_owned(), content.clone(), None, None)),
+                    None => me.as_any().downcast_ref::<TaggedFileSong>().cloned(),
+                };
+                let action = match song {
+                    Some(mut song) => {
+                        match song.set_tag(TagField::Title, Some(content)) {
+                            Ok(()) => {
+                                // the file might have had other tags without a title, so they are read back from it
+                                let song = match TaggedFileSong::from_file_path(song.path().to_owned().into()) {
+                                    Ok(Some(read)) => read,
+                                    _ => song,
+                                };
+                                *me = song.into();
+                                ContentManagerAction::RequestSave
+                            }
+                            Err(err) => {
+                                let message = format!("could not edit the tags of '{}': {err}", song.path());
+                                ContentManagerAction::Notify { notif: Notification::new(Severity::Error, message) }
+                            }
+                        }
+                    }
+                    None => {
+                        let message = format!("'{}' changed while it was being edited", me.as_display().title());
+                        ContentManagerAction::Notify { notif: Notification::new(Severity::Error, message) }
+                    }
+                };
+                vec![
+                    ContentManagerAction::PopContentStack, // typing
+                    action,
+                ].into()
+            })),
+        }
+    }
+    fn num_editables(&self, _: &StateContext) -> usize {
+        1
+    }
+    fn display_editables(&self, _: &StateContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw(format!("Edit: {}", self.title)));
+        lb.items = vec![
+            Item {
+                text: vec![Line::new(Span::from(format!("{:#?}: {}", TagField::Title, self.title)))],
+                selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+            },
+        ];
+        lb
+    }
+}
@@ -18,10 +18,22 @@ use crate::{
             ContentManagerAction,
             RustParallelAction,
         },
-        song::traits::{
-            SongTrait,
-            Func,
-            SongDisplay,
+        providers::downloads::{
+            self,
+            JobID,
+        },
+        register::SongID,
+        song::{
+            traits::{
+                SongTrait,
+                Func,
+                SongDisplay,
+            },
+            tagged_file_song::{
+                self,
+                TaggedFileSong,
+                TagField,
+            },
         },
     },
     service::{
@@ -31,6 +43,7 @@ use crate::{
             item::{
                 Json,
                 Ytdl,
+                YtdlDownloader,
             },
         },
         yt::ytdl::YtdlSong,
@@ -85,6 +98,16 @@ impl YtSong {
         }.into();
         Ok(action)
     }
+
+    /// ytdl's tags come from the video, but these come from ytmusic (if the song was found there)
+    fn write_tags(&self, path: &str) -> Result<()> {
+        tagged_file_song::write_tag(path, TagField::Title, Some(&self.title))?;
+        tagged_file_song::write_tag(path, TagField::Artist, Some(&self.artist))?;
+        if let Some(album) = self.album.as_ref() {
+            tagged_file_song::write_tag(path, TagField::Album, Some(album))?;
+        }
+        Ok(())
+    }
 }
 
 #[typetag::serde]
@@ -136,9 +159,9 @@ impl SongTrait for YtSong {
             callback(best_audio_url)
         }))
     }
-    fn play(&self) -> Result<ContentManagerAction> {
-        self.get_uri(Box::new(|uri: String| {
-            Ok(ContentManagerAction::PlaySongURI { uri })
+    fn play(&self, id: SongID) -> Result<ContentManagerAction> {
+        self.get_uri(Box::new(move |uri: String| {
+            Ok(ContentManagerAction::PlaySongURI { id, uri })
         }))   
     }
     fn show_art(&self) -> Result<ContentManagerAction> {
@@ -174,6 +197,42 @@ impl SongTrait for YtSong {
             ].into())
         }))
     }
+    fn save_to_path(&self, job: JobID) -> Result<ContentManagerAction> {
+        let song = self.clone();
+        let action = PyAction::ExecJob {
+            job,
+            code: PyCodeBuilder::new()
+            .threaded()
+            .func(
+                format!("
+                    import yt_dlp
+                    def hook(d):
+                        if res['cancel']:
+                            raise Exception('cancelled')
+                        total = d.get('total_bytes') or d.get('total_bytes_estimate')
+                        if d['status'] == 'downloading' and total:
+                            res['progress'] = d['downloaded_bytes'] / total
+                    # a new instance for every download, so the hooks don't get mixed up
+                    with yt_dlp.YoutubeDL({{**ytdl.params, 'progress_hooks': [hook]}}) as y:
+                        ytdl_data = y.extract_info(url='https://youtu.be/{}', download=True)
+                    return ytdl_data['requested_downloads'][0]['filepath']
+                ", self.id),
+                Some(vec![
+                    YtdlDownloader::new("ytdl").into(),
+                ]),
+            )
+            .build()?,
+            progress: Box::new(move |progress| downloads::progress_action(job, progress)),
+            callback: Box::new(move |res: Result<String>| {
+                let res = res.and_then(|path| {
+                    song.write_tags(&path)?;
+                    TaggedFileSong::from_file_path(path.into())?.context("downloaded song has no tags")
+                });
+                Ok(downloads::finished_action(job, res))
+            }),
+        }.into();
+        Ok(action)
+    }
     fn get_showable_info(&self) -> Box<dyn Iterator<Item = std::borrow::Cow<'static, str>>> {
         Box::new([
             format!("title: {}", self.title),
@@ -184,6 +243,9 @@ impl SongTrait for YtSong {
     fn as_display(&self) -> &dyn SongDisplay {
         self
     }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
 
 impl SongDisplay for YtSong {
@@ -198,3 +260,4 @@ impl SongDisplay for YtSong {
     }
 }
 
+
@@ -12,25 +12,142 @@ use serde::{
     Serialize,
 };
 use once_cell::sync::OnceCell;
+
+use crate::{
+    app::keymap::Keymap,
+    service::db::storage::DBFormat,
+};
 use dirs;
 use std::{
     path::PathBuf,
-    io::Read,
+    collections::HashMap,
 };
+use anyhow::{
+    Result,
+    bail,
+    Context,
+};
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
 
+/// panics if the config could not be loaded. call init_config at the start to get a proper error instead
 pub fn config() -> &'static Config {
-    static CONFIG: OnceCell<Config> = OnceCell::new();
-    CONFIG.get_or_init(|| {
-        let dev_config_path = "./config/config.toml";
-        let mut dev_config_file = std::fs::File::open(dev_config_path).unwrap();
-        let mut buf = String::new();
-        dev_config_file.read_to_string(&mut buf).unwrap();
-        let config = toml::from_str::<ConfigBuilder>(&buf).unwrap();
-        dbg!(&config, toml::to_string(&config), Config::from(config.clone()));
-        config.into()
-    })
+    CONFIG.get_or_init(|| Config::discover(None).unwrap())
+}
+
+/// path passed using the --config flag takes priority over everything else
+pub fn init_config(path: Option<PathBuf>) -> Result<()> {
+    let config = Config::discover(path)?;
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+/// writes the default config with comments to the provided path, or the default config path
+pub fn write_default_config(path: Option<PathBuf>) -> Result<PathBuf> {
+    let path = match path {
+        Some(path) => path,
+        None => default_config_path().context("could not find the config directory")?,
+    };
+    if path.exists() {
+        bail!("{} already exists", path.to_string_lossy());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, DEFAULT_CONFIG)?;
+    Ok(path)
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("musiman/config.toml"))
 }
 
+const DEFAULT_CONFIG: &str = r#"# musiman config
+# every option is optional. the commented out values are the defaults
+# paths starting with ~/ are relative to the home directory
+
+# directory opened in new file explorers
+# file_explorer_default_path = "~/"
+
+# cookies/headers used for ytmusic (see ytmusicapi docs)
+# ytmusic_cookies_path = "~/.config/musiman/headers.json"
+
+# extension of the songs downloaded from youtube
+# prefered_song_ext = "mp3"
+
+# directory where the songs are downloaded to (defaults to the system music directory)
+# music_path = "~/Music"
+
+# seconds skipped by seek_forward/seek_backward
+# seek_step = 10.0
+
+# percent changed by volume_up/volume_down
+# volume_step = 5
+
+# the song that was playing when the app was closed continues from where it was. this starts it paused instead
+# resume_paused = false
+
+# how many songs are downloaded at the same time. the rest wait in the downloads view
+# max_concurrent_downloads = 3
+
+# format of the database. "yaml" can be read and edited by hand, "msgpack" is a lot smaller and faster for big libraries.
+# the default db_path uses the extension of the format. a db_path with a .yaml or .msgpack extension is always read in that format.
+# an existing db can be converted using --convert-db <from> <to>
+# db_format = "yaml"
+
+# path of the database
+# db_path = "~/.config/musiman/db.yaml"
+
+# how many backups of the db are kept next to it (a new one is made on the first save of every run, and then once a day)
+# db_backups = 5
+
+# the db is saved every this many seconds, and also soon after edits like paste, cut or new playlists. 0 disables autosave.
+# the db is not saved if musiman crashes, so anything since the last autosave is lost then
+# autosave_interval = 300
+
+# path of the index of the songs in music_path (used by the library)
+# library_index_path = "~/.config/musiman/library_index.json"
+
+# key bindings are added on top of the default ones.
+# keys can have modifiers (ctrl-, alt-, shift-) and can be sequences of keys seperated by spaces.
+# special keys: esc enter tab backtab backspace delete insert space up down left right home end pageup pagedown f1-f12
+# bind a key to "none" to remove it. a key can't be bound if it is the start of another binding ("g" and "g g")
+# commands: selection_up selection_down enter_selected back open_menu open_edit edit_selected toggle_yank cancel_yank
+#   clear_edit_history cut copy paste_above paste_below undo redo notifications
+#   toggle_pause seek_forward seek_backward volume_up volume_down toggle_mute next_song prev_song
+#   quit debug_input command_line filter
+[keymap]
+# "up" = "selection_up"
+# "down" = "selection_down"
+# "right" = "enter_selected"
+# "left" = "back"
+# "G" = "open_menu"
+# "E" = "open_edit"
+# "e" = "edit_selected"
+# "y" = "toggle_yank"
+# "esc" = "cancel_yank"
+# "Y" = "clear_edit_history"
+# "X" = "cut"
+# "C" = "copy"
+# "v" = "paste_above"
+# "V" = "paste_below"
+# "z" = "undo"
+# "Z" = "redo"
+# "N" = "notifications"
+# "p" = "toggle_pause"
+# "k" = "seek_forward"
+# "j" = "seek_backward"
+# "+" = "volume_up"
+# "-" = "volume_down"
+# "m" = "toggle_mute"
+# "l" = "next_song"
+# "h" = "prev_song"
+# "q" = "quit"
+# "d" = "debug_input"
+# ":" = "command_line"
+# "/" = "filter"
+"#;
+
 type MaybeString = Option<String>;
 type MaybePath = Option<PathBuf>;
 
@@ -40,7 +157,16 @@ struct ConfigBuilder {
     ytmusic_cookies_path: MaybePath,
     prefered_song_ext: MaybeString,
     music_path: MaybePath,
+    max_concurrent_downloads: Option<usize>,
+    seek_step: Option<f64>,
+    volume_step: Option<u8>,
+    resume_paused: Option<bool>,
+    db_format: Option<DBFormat>,
     db_path: MaybePath,
+    db_backups: Option<usize>,
+    autosave_interval: Option<u64>,
+    library_index_path: MaybePath,
+    keymap: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -49,7 +175,18 @@ pub struct Config {
     pub ytmusic_cookies_path: MaybePath,
     pub prefered_song_ext: String,
     pub music_path: PathBuf,
+    pub max_concurrent_downloads: usize,
+    pub seek_step: f64,
+    pub volume_step: u8,
+    pub resume_paused: bool,
+    pub db_format: DBFormat,
     pub db_path: PathBuf, // TODO: have a general config path and have this relative to that
+    pub db_backups: usize,
+    /// in seconds
+    pub autosave_interval: u64,
+    pub library_index_path: PathBuf,
+    #[serde(skip)]
+    pub keymap: Keymap,
 }
 impl Default for Config {
     fn default() -> Self {
@@ -57,12 +194,72 @@ impl Default for Config {
             file_explorer_default_path: expand_path("~/"),
             ytmusic_cookies_path: None,
             prefered_song_ext: "mp3".into(),
-            music_path: dirs::audio_dir().unwrap(), // FIX: not available in termux ??
-            db_path: dirs::config_dir().unwrap().join("musiman/db.yaml")
+            music_path: dirs::audio_dir().unwrap_or(expand_path("~/Music")), // audio_dir is not available in termux
+            max_concurrent_downloads: 3,
+            seek_step: 10.0,
+            volume_step: 5,
+            resume_paused: false,
+            db_format: Default::default(),
+            db_path: dirs::config_dir().unwrap().join("musiman/db.yaml"),
+            db_backups: 5,
+            autosave_interval: 300,
+            library_index_path: dirs::config_dir().unwrap().join("musiman/library_index.json"),
+            keymap: Default::default(),
         }
     }
 }
 
+impl Config {
+    /// looks for the config in this order:
+    /// - the path passed as a cli arg
+    /// - $MUSIMAN_CONFIG
+    /// - $XDG_CONFIG_HOME/musiman/config.toml
+    /// - ./config/config.toml (dev setup)
+    /// if none of these exist, the default config is used.
+    /// paths that are explicitly provided (cli arg and env var) must exist
+    fn discover(cli_path: Option<PathBuf>) -> Result<Self> {
+        let env_path = std::env::var_os("MUSIMAN_CONFIG").map(PathBuf::from);
+        let explicit = cli_path.is_some() || env_path.is_some();
+        let candidates = cli_path
+        .or(env_path)
+        .into_iter()
+        .chain(
+            [default_config_path(), Some(PathBuf::from("./config/config.toml"))]
+            .into_iter()
+            .flatten()
+            .filter(|_| !explicit)
+        )
+        .collect::<Vec<_>>();
+
+        let tried = candidates.iter()
+        .map(|p| format!("  {}", p.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join("\n");
+        let path = match candidates.iter().find(|p| p.is_file()) {
+            Some(path) => path,
+            None if explicit => bail!("config file not found. paths tried:\n{tried}"),
+            None => {
+                debug!("no config file found, using defaults. paths tried:\n{tried}");
+                return Ok(Self::default());
+            }
+        };
+        let buf = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read config file {}", path.to_string_lossy()))?;
+        let mut config = toml::from_str::<ConfigBuilder>(&buf)
+        .with_context(|| format!("could not parse config file {}", path.to_string_lossy()))?;
+        let keymap = config.keymap
+        .take()
+        .map(Keymap::with_bindings)
+        .transpose()
+        .with_context(|| format!("invalid keymap in config file {}", path.to_string_lossy()))?
+        .unwrap_or_default();
+        Ok(Config {
+            keymap,
+            ..config.into()
+        })
+    }
+}
+
 impl From<ConfigBuilder> for Config {
     fn from(cb: ConfigBuilder) -> Self {
         let def = Self::default();
@@ -82,9 +279,38 @@ impl From<ConfigBuilder> for Config {
             .map(expand_path)
             .unwrap_or(def.music_path),
 
+            max_concurrent_downloads: cb.max_concurrent_downloads
+            .filter(|&n| n > 0)
+            .unwrap_or(def.max_concurrent_downloads),
+
+            seek_step: cb.seek_step
+            .filter(|&s| s > 0.0)
+            .unwrap_or(def.seek_step),
+
+            volume_step: cb.volume_step
+            .unwrap_or(def.volume_step),
+
+            resume_paused: cb.resume_paused
+            .unwrap_or(def.resume_paused),
+
+            db_format: cb.db_format
+            .unwrap_or(def.db_format),
+
             db_path: cb.db_path
             .map(expand_path)
-            .unwrap_or(def.db_path),
+            .unwrap_or(def.db_path.with_extension(cb.db_format.unwrap_or(def.db_format).extension())),
+
+            db_backups: cb.db_backups
+            .unwrap_or(def.db_backups),
+
+            autosave_interval: cb.autosave_interval
+            .unwrap_or(def.autosave_interval),
+
+            library_index_path: cb.library_index_path
+            .map(expand_path)
+            .unwrap_or(def.library_index_path),
+
+            keymap: def.keymap,
         }
     }
 }
@@ -7,20 +7,36 @@ use crate::{
 };
 
 use serde::{Deserialize, Serialize};
-use serde_yaml;
 use std::{
     fs::File,
     io::{
-        BufReader,
-        BufWriter, Read, Write,
+        BufWriter,
+        Write,
     },
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::Mutex,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use anyhow::{
+    Result,
+    bail,
+    Context,
 };
-use anyhow::Result;
 
 use crate::{
     content::{
         providers::ContentProvider,
         song::Song,
+        manager::manager::{
+            Volume,
+            Session,
+        },
         register::{
             ContentRegister,
             ContentProviderID,
@@ -30,36 +46,184 @@ use crate::{
     service::{
         editors::EditManager,
         config::config,
+        db::{
+            migrations::{
+                self,
+                NewerDB,
+            },
+            storage::DBFormat,
+        },
     },
 };
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct DBHandler {
+    /// the layout version of the db (see migrations::DB_VERSION)
+    pub version: u32,
     pub songs: ContentRegister<Song, SongID>,
     pub content_providers: ContentRegister<ContentProvider, ContentProviderID>,
     pub main_provider: ContentProviderID,
     pub edit_manager: EditManager,
+    #[serde(default)]
+    pub volume: Volume,
+    /// None for dbs saved before sessions were saved
+    #[serde(default)]
+    pub session: Option<Session>,
+    /// the backup this was loaded from (if the db itself could not be read)
+    #[serde(skip)]
+    pub recovered_from: Option<PathBuf>,
+    /// the version the db was at, if it had to be upgraded
+    #[serde(skip)]
+    pub migrated_from: Option<u32>,
 }
 
 impl DBHandler {
+    /// if the db can't be read, the newest backup that can be read is used instead (and recovered_from is set).
+    /// if none of them can be read, the error of the db is returned.
+    /// older dbs are upgraded to the current version, and a copy of the old one is kept as "<db>.v<old version>"
     pub fn try_load() -> Result<Option<Self>> {
         let db_path = config().db_path.as_path();
-        let file = match File::open(db_path) {
-            Ok(file) => file,
-            Err(_) => return Ok(None), // no problem is file does not exist
+        if !db_path.exists() {
+            let other = [DBFormat::Yaml, DBFormat::Msgpack]
+            .into_iter()
+            .map(|f| db_path.with_extension(f.extension()))
+            .find(|p| p != db_path && p.exists());
+            if let Some(other) = other {
+                bail!(
+                    "there is no db at {db}, but there is one at {other}. convert it using: musiman --convert-db {other} {db}",
+                    db = db_path.to_string_lossy(),
+                    other = other.to_string_lossy(),
+                );
+            }
+            return Ok(None); // no problem is file does not exist
+        }
+        let format = db_format(db_path);
+        let err = match Self::read(db_path, format) {
+            Ok(dbh) => {
+                if let Some(v) = dbh.migrated_from {
+                    let old = with_suffix(db_path, &format!(".v{v}"));
+                    if !old.exists() {
+                        std::fs::copy(db_path, &old)
+                        .with_context(|| format!("could not keep a copy of the old db at {}", old.to_string_lossy()))?;
+                    }
+                }
+                return Ok(Some(dbh));
+            }
+            Err(err) => err,
         };
-        let mut red = BufReader::new(file);
-        let mut buf = String::new();
-        red.read_to_string(&mut buf)?;
-        let dbh = serde_yaml::from_str(&buf)?;
+        if err.downcast_ref::<NewerDB>().is_some() {
+            return Err(err);
+        }
+        error!("{err:?}");
+        for backup in backups(db_path).into_iter().rev() {
+            match Self::read(&backup, format) {
+                Ok(mut dbh) => {
+                    dbh.recovered_from = Some(backup);
+                    return Ok(Some(dbh));
+                }
+                Err(err) => error!("{err:?}"),
+            }
+        }
+        Err(err)
+    }
+
+    /// older dbs are upgraded to the current version
+    pub fn read(path: &Path, format: DBFormat) -> Result<Self> {
+        let bytes = std::fs::read(path)
+        .with_context(|| format!("could not open db {}", path.to_string_lossy()))?;
+        let mut doc = format.storage().decode(&bytes)
+        .with_context(|| format!("could not parse db {}", path.to_string_lossy()))?;
+        let version = migrations::migrate(&mut doc)?;
+        let mut dbh: Self = serde_yaml::from_value(doc)
+        .with_context(|| format!("could not parse db {}", path.to_string_lossy()))?;
+        if version < migrations::DB_VERSION {
+            dbh.migrated_from = Some(version);
+        }
         Ok(dbh)
     }
 
+    /// the db is written to a temp file which then replaces the db, so a crash while saving never leaves a half written db.
+    /// the old db is kept as a timestamped backup on the first save of a run and then once every BACKUP_INTERVAL
+    /// (only the newest config().db_backups of them are kept). autosave saves often, so a backup on every save would only cover the last few minutes
     pub fn save(&self) -> Result<()> {
         let db_path = config().db_path.as_path();
-        let mut w = BufWriter::new(File::create(db_path)?);
-        let yaml = serde_yaml::to_string(self)?;
-        write!(w, "{yaml}")?;
+        let bytes = db_format(db_path).storage().encode(self)?;
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let temp_path = with_suffix(db_path, ".tmp");
+        {
+            let file = File::create(&temp_path)
+            .with_context(|| format!("could not write db {}", temp_path.to_string_lossy()))?;
+            let mut w = BufWriter::new(file);
+            w.write_all(&bytes)?;
+            w.flush()?;
+            w.get_ref().sync_all()?;
+        }
+
+        let mut last_backup = LAST_BACKUP.lock().unwrap();
+        let backup_due = last_backup.map(|t| t.elapsed() >= BACKUP_INTERVAL).unwrap_or(true);
+        if db_path.exists() && config().db_backups > 0 && backup_due {
+            let stamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+            let backup = with_suffix(db_path, &format!("{BACKUP_SUFFIX}{stamp}"));
+            std::fs::copy(db_path, &backup)
+            .with_context(|| format!("could not backup db to {}", backup.to_string_lossy()))?;
+            let backups = backups(db_path);
+            let extra = backups.len().saturating_sub(config().db_backups);
+            for old in backups.into_iter().take(extra) {
+                if let Err(err) = std::fs::remove_file(&old) {
+                    error!("could not remove old db backup {}: {err}", old.to_string_lossy());
+                }
+            }
+            *last_backup = Some(Instant::now());
+        }
+
+        std::fs::rename(&temp_path, db_path)
+        .with_context(|| format!("could not replace db {}", db_path.to_string_lossy()))?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// picked using the extension of the db. config().db_format is only used if the extension is not a known one
+fn db_format(db_path: &Path) -> DBFormat {
+    DBFormat::from_path(db_path).unwrap_or(config().db_format)
+}
+
+/// when the last backup was made in this run
+static LAST_BACKUP: Mutex<Option<Instant>> = Mutex::new(None);
+const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// backups are named like "db.yaml.backup-2023-01-31_18-30-00" and live next to the db (in the same format)
+const BACKUP_SUFFIX: &str = ".backup-";
+
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// oldest first (the timestamps sort the same way as the names)
+fn backups(db_path: &Path) -> Vec<PathBuf> {
+    let prefix = with_suffix(db_path, BACKUP_SUFFIX);
+    let prefix = match prefix.file_name().and_then(|n| n.to_str()) {
+        Some(p) => p.to_owned(),
+        None => return vec![],
+    };
+    let dir = match db_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut backups = match std::fs::read_dir(dir) {
+        Ok(entries) => {
+            entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.file_name().and_then(|n| n.to_str()).map(|n| n.starts_with(&prefix)).unwrap_or(false))
+            .collect::<Vec<_>>()
+        }
+        Err(_) => vec![],
+    };
+    backups.sort();
+    backups
+}
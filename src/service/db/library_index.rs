@@ -0,0 +1,217 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    fs::File,
+    io::{
+        BufReader,
+        BufWriter,
+        Write,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::UNIX_EPOCH,
+};
+use anyhow::{
+    Result,
+    Context,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    content::song::{
+        Song,
+        tagged_file_song::{
+            TaggedFileSong,
+            TagField,
+        },
+        untagged_file_song::UntaggedFileSong,
+    },
+    service::db::handler::with_suffix,
+};
+
+/// files with any other extension are not looked at
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "opus", "m4a", "aac", "wav", "wma", "webm", "mka"];
+
+/// bump this when IndexEntry changes, so that older indices are rescanned from scratch
+const INDEX_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// seconds since the unix epoch
+    pub mtime: u64,
+    pub size: u64,
+    /// every tag of the file that TaggedFileSong knows about
+    pub tags: HashMap<TagField, String>,
+}
+impl IndexEntry {
+    /// tags are read with lofty. files without a title are still indexed, just without tags
+    fn read(path: &str, mtime: u64, size: u64) -> Self {
+        let song = TaggedFileSong::from_file_path(path.into())
+        .map_err(|err| debug!("could not read tags of {path}: {err}"))
+        .ok()
+        .flatten();
+        let tags = song.map(|song| {
+            TagField::iter()
+            .iter()
+            .filter_map(|&field| song.get_tag(field).map(|v| (field, v.to_owned())))
+            .collect()
+        })
+        .unwrap_or_default();
+        Self {
+            mtime,
+            size,
+            tags,
+        }
+    }
+
+    pub fn song(&self, path: &str) -> Song {
+        match TaggedFileSong::from_tags(path.to_owned(), |field| self.tags.get(&field).cloned()) {
+            Some(song) => song.into(),
+            None => UntaggedFileSong::from_file_path(path.into()).into(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ScanStats {
+    /// paths that are new or were modified since the last scan
+    pub changed: HashSet<String>,
+    pub removed: usize,
+}
+
+/// every audio file under a directory, keyed by its path. saved as json so that
+/// rescans only need to read the tags of files that changed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LibraryIndex {
+    version: u32,
+    pub entries: HashMap<String, IndexEntry>,
+}
+
+impl LibraryIndex {
+    /// an empty index if the file does not exist yet, or if it was written by an older version
+    pub fn load(path: &Path) -> Result<Self> {
+        let empty = Self {
+            version: INDEX_VERSION,
+            entries: Default::default(),
+        };
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return Ok(empty),
+        };
+        let value: serde_json::Value = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("could not parse library index {}", path.to_string_lossy()))?;
+        if value.get("version").and_then(|v| v.as_u64()) != Some(INDEX_VERSION as u64) {
+            debug!("library index {} is outdated. rescanning everything", path.to_string_lossy());
+            return Ok(empty);
+        }
+        let index = serde_json::from_value(value)
+        .with_context(|| format!("could not parse library index {}", path.to_string_lossy()))?;
+        Ok(index)
+    }
+
+    /// written to a temp file which then replaces the index, so a crash while saving never leaves a half written index
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp_path = with_suffix(path, ".tmp");
+        {
+            let file = File::create(&temp_path)
+            .with_context(|| format!("could not write library index {}", temp_path.to_string_lossy()))?;
+            let mut w = BufWriter::new(file);
+            serde_json::to_writer(&mut w, self)?;
+            w.flush()?;
+            w.get_ref().sync_all()?;
+        }
+        std::fs::rename(&temp_path, path)
+        .with_context(|| format!("could not replace library index {}", path.to_string_lossy()))?;
+        Ok(())
+    }
+
+    /// walks the directory recursively. only files whose mtime or size changed are read again.
+    /// symlinked directories are not followed. fails if the root can't be read (eg. the drive is not mounted),
+    /// and the entries under subdirectories that can't be read are kept as they are
+    pub fn scan(&mut self, root: &Path) -> Result<ScanStats> {
+        let mut stats = ScanStats::default();
+        let mut seen = HashSet::new();
+        let mut unreadable: Vec<PathBuf> = vec![];
+        let mut dirs = vec![root.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) if dir.as_path() == root => {
+                    return Err(err).with_context(|| format!("could not read music directory {}", root.to_string_lossy()));
+                }
+                Err(err) => {
+                    error!("could not read {}: {err}", dir.to_string_lossy());
+                    unreadable.push(dir);
+                    continue;
+                }
+            };
+            for e in entries.filter_map(|e| e.ok()) {
+                let file_type = match e.file_type() {
+                    Ok(t) => t,
+                    Err(_) => continue,
+                };
+                let path = e.path();
+                if file_type.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let is_audio = path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false);
+                if !is_audio {
+                    continue;
+                }
+                let metadata = match std::fs::metadata(&path) { // follows file symlinks
+                    Ok(m) if m.is_file() => m,
+                    _ => continue,
+                };
+                let mtime = metadata.modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+                let size = metadata.len();
+                let path = match path.to_str() {
+                    Some(p) => p.to_owned(),
+                    None => continue,
+                };
+
+                let unchanged = self.entries
+                .get(&path)
+                .map(|e| e.mtime == mtime && e.size == size)
+                .unwrap_or(false);
+                if !unchanged {
+                    let entry = IndexEntry::read(&path, mtime, size);
+                    self.entries.insert(path.clone(), entry);
+                    stats.changed.insert(path.clone());
+                }
+                seen.insert(path);
+            }
+        }
+
+        let before = self.entries.len();
+        self.entries.retain(|path, _| {
+            seen.contains(path) || unreadable.iter().any(|dir| Path::new(path).starts_with(dir))
+        });
+        stats.removed = before - self.entries.len();
+        Ok(stats)
+    }
+}
@@ -0,0 +1,134 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use anyhow::{
+    Result,
+    anyhow,
+};
+use serde_yaml::{
+    Mapping,
+    Value,
+};
+
+use crate::content::manager::manager::Volume;
+
+/// the version of the db that this build writes. when the layout of the db changes (a field or a provider/song type
+/// gets renamed, something gets restructured), bump this and add the migration for the old version to MIGRATIONS
+pub const DB_VERSION: u32 = 1;
+
+/// MIGRATIONS[n] upgrades a version n db to version n + 1. they work on the raw yaml, so the old layout does not need any
+/// rust types. typetag'd providers and songs look like { TypeName: { ..fields } } in there
+const MIGRATIONS: [fn(&mut Mapping) -> Result<()>; DB_VERSION as usize] = [
+    v0_to_v1,
+];
+
+/// a db written by a newer version of musiman. it is not touched (or replaced by a backup), as it would lose whatever the newer version added
+#[derive(Debug)]
+pub struct NewerDB {
+    pub version: u32,
+}
+impl std::fmt::Display for NewerDB {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the db is version {}, but this build only knows upto version {DB_VERSION}", self.version)
+    }
+}
+impl std::error::Error for NewerDB {}
+
+/// dbs without a version are version 0
+pub fn version(db: &Value) -> Result<u32> {
+    match db.get("version") {
+        None => Ok(0),
+        Some(v) => {
+            v.as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(anyhow!("bad db version {v:?}"))
+        }
+    }
+}
+
+/// upgrades the db one version at a time till it is DB_VERSION. returns the version it was at
+pub fn migrate(db: &mut Value) -> Result<u32> {
+    let from = version(db)?;
+    if from > DB_VERSION {
+        return Err(NewerDB { version: from }.into());
+    }
+    let map = db.as_mapping_mut().ok_or(anyhow!("the db is not a yaml mapping"))?;
+    for (v, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migration(map).map_err(|err| err.context(format!("could not upgrade the db from version {v}")))?;
+        map.insert("version".into(), (v as u64 + 1).into());
+        debug!("db upgraded to version {}", v + 1);
+    }
+    Ok(from)
+}
+
+/// dbs from before the db had a version. volume and session were added later (as optional fields), so they get written out here
+fn v0_to_v1(db: &mut Mapping) -> Result<()> {
+    if !db.contains_key(&"volume".into()) {
+        db.insert("volume".into(), serde_yaml::to_value(Volume::default())?);
+    }
+    if !db.contains_key(&"session".into()) {
+        db.insert("session".into(), Value::Null);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::service::db::{
+        handler::DBHandler,
+        storage::DBFormat,
+    };
+
+    /// v<n>.yaml is a db as version n wrote it. add one for every new version
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/db").join(name)
+    }
+
+    fn load(name: &str) -> Value {
+        serde_yaml::from_str(&std::fs::read_to_string(fixture(name)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn upgrades_unversioned_db() {
+        let mut db = load("v0.yaml");
+        assert_eq!(version(&db).unwrap(), 0);
+        assert_eq!(migrate(&mut db).unwrap(), 0);
+        assert_eq!(version(&db).unwrap(), DB_VERSION);
+
+        let dbh = DBHandler::read(&fixture("v0.yaml"), DBFormat::Yaml).unwrap();
+        assert_eq!(dbh.version, DB_VERSION);
+        assert_eq!(dbh.migrated_from, Some(0));
+        assert_eq!(dbh.volume, Volume::default());
+        assert!(dbh.session.is_none());
+    }
+
+    #[test]
+    fn leaves_current_db_unchanged() {
+        let name = format!("v{DB_VERSION}.yaml");
+        let original = load(&name);
+        let mut db = original.clone();
+        assert_eq!(migrate(&mut db).unwrap(), DB_VERSION);
+        assert_eq!(db, original);
+
+        let dbh = DBHandler::read(&fixture(&name), DBFormat::Yaml).unwrap();
+        assert_eq!(dbh.migrated_from, None);
+        assert_eq!(dbh.volume, Volume { level: 40, muted: true });
+        assert!(dbh.session.is_some());
+    }
+
+    #[test]
+    fn rejects_newer_db() {
+        let mut db = load("newer.yaml");
+        let err = migrate(&mut db).unwrap_err();
+        assert_eq!(err.downcast_ref::<NewerDB>().map(|e| e.version), Some(999));
+
+        let err = DBHandler::read(&fixture("newer.yaml"), DBFormat::Yaml).unwrap_err();
+        assert!(err.downcast_ref::<NewerDB>().is_some());
+    }
+}
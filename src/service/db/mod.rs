@@ -1,3 +1,6 @@
 
 pub mod handler;
 pub mod musimanager_db;
+pub mod library_index;
+pub mod migrations;
+pub mod storage;
@@ -6,8 +6,46 @@ use crate::{
     error,
 };
 
+use std::{
+    collections::HashMap,
+    path::Path,
+};
+use anyhow::Result;
 use serde::{self, Serialize, Deserialize};
 
+use crate::{
+    content::{
+        manager::{
+            action::{
+                ContentManagerAction,
+                RustParallelAction,
+            },
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+        providers::{
+            ContentProvider,
+            main_provider::{
+                get_or_add_artist_provider,
+                get_or_add_playlist_provider,
+            },
+            traits::SongProvider,
+            artist::Artist,
+            playlist::Playlist,
+            queue::Queue,
+        },
+        register::{
+            SongID,
+            ContentProviderID,
+        },
+        song::{
+            Song,
+            yt_song::YtSong,
+            tagged_file_song::TaggedFileSong,
+            untagged_file_song::UntaggedFileSong,
+        },
+    },
+};
 
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -46,7 +84,7 @@ pub struct MusiAlbum {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MusiSong { // in python, everything here is marked optional
     title: String,
-    #[serde(rename(deserialize = "key"))]
+    #[serde(rename = "key")]
     id: String,
     artist_name: Option<String>,
     info: MusiSongInfo,
@@ -68,22 +106,414 @@ pub struct MusiSongInfo {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MusiSongProvider {
-    #[serde(rename(deserialize = "data_list"))]
+    #[serde(rename = "data_list")]
     songs: Vec<MusiSong>,
-    #[serde(rename(deserialize = "name"))]
+    #[serde(rename = "name")]
     title: String,
     current_index: i64,
 }
 
-pub fn test() -> anyhow::Result<()> {
-    use std::io::Read;
-    
-    let musitracker_path = "/home/issac/0Git/musimanager/db/musitracker.json";
-    let mut musitracker = std::fs::File::open(musitracker_path).unwrap();
-    let mut buf = String::new();
-    musitracker.read_to_string(&mut buf).unwrap();
-    let musidb = serde_json::from_str::<MusimanagerDB>(&buf);
-    dbg!(&musidb);
-    // debug!("{}", serde_yaml::to_string(&musidb?)?);
-    Ok(())
+impl MusimanagerDB {
+    pub fn from_path(path: &str) -> Result<Self> {
+        let buf = std::fs::read_to_string(path)?;
+        let db = serde_json::from_str(&buf)?;
+        Ok(db)
+    }
+
+    pub fn save_to_path(&self, path: &str) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// collects artists, playlists and queues from the providers saved in MainProvider
+    fn from_content_manager(ch: &ContentManager) -> Self {
+        let children = |id: Option<ContentProviderID>| {
+            id.map(|id| {
+                ch.get_provider(id)
+                .as_provider()
+                .unwrap()
+                .providers()
+                .cloned()
+                .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+        };
+        let songs = |cp: &ContentProvider| {
+            cp.as_song_provider()
+            .map(|sp| {
+                sp.songs()
+                .filter_map(|&id| MusiSong::from_song(ch.get_song(id)))
+                .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+        };
+
+        let mp = ch.get_main_provider();
+        let artists = children(mp.artist_provider)
+        .into_iter()
+        .map(|id| ch.get_provider(id))
+        .map(|cp| {
+            let name = cp.as_display().get_name().into_owned();
+            MusiArtist {
+                keys: vec![name.clone()],
+                name,
+                check_stat: false,
+                ignore_no_songs: false,
+                name_confirmation_status: false,
+                songs: songs(cp),
+                known_albums: vec![],
+                keywords: vec![],
+                non_keywords: vec![],
+                search_keywords: vec![],
+                last_auto_search: None,
+            }
+        })
+        .collect();
+        let playlists = children(mp.playlist_provider)
+        .into_iter()
+        .map(|id| ch.get_provider(id))
+        .map(|cp| MusiSongProvider {
+            songs: songs(cp),
+            title: cp.as_display().get_name().into_owned(),
+            current_index: 0,
+        })
+        .collect();
+        let queues = children(Some(mp.queue_provider))
+        .into_iter()
+        .map(|id| ch.get_provider(id))
+        .filter_map(|cp| cp.as_any().downcast_ref::<Queue>().map(|q| (cp, q)))
+        .map(|(cp, q)| {
+            let songs = songs(cp);
+            // songs that could not be exported shift the index, so it is only kept if nothing was skipped
+            let current_index = q.currently_playing
+            .filter(|_| songs.len() == q.songs.len())
+            .unwrap_or(0) as i64;
+            MusiSongProvider {
+                songs,
+                title: q.name.as_ref().to_owned(),
+                current_index,
+            }
+        })
+        .collect();
+
+        Self {
+            artists,
+            auto_search_artists: vec![],
+            playlists,
+            queues,
+        }
+    }
+
+    /// converts everything into songs and lists of indices into those songs.
+    /// songs are deduplicated using their video id
+    fn convert(self) -> ImportedDB {
+        let mut db = ImportedDB::default();
+        let artists = self.artists
+        .into_iter()
+        .chain(self.auto_search_artists.into_iter())
+        .map(|a| ImportedList {
+            name: a.name,
+            songs: a.songs.into_iter().map(|s| db.add_song(s)).collect(),
+            current_index: None,
+        })
+        .collect();
+        let playlists = self.playlists
+        .into_iter()
+        .map(|p| db.convert_list(p))
+        .collect();
+        let queues = self.queues
+        .into_iter()
+        .map(|q| db.convert_list(q))
+        .collect();
+        db.artists = artists;
+        db.playlists = playlists;
+        db.queues = queues;
+        db
+    }
+}
+
+impl MusiSong {
+    /// file songs have no video id, so the file name is used as the key. (musimanager names the downloaded songs by their video id)
+    fn from_song(song: &Song) -> Option<Self> {
+        let (key, path) = if let Some(s) = song.as_any().downcast_ref::<YtSong>() {
+            (s.id.clone(), None)
+        } else if let Some(s) = song.as_any().downcast_ref::<TaggedFileSong>() {
+            (Self::key_from_path(s.path()), Some(s.path().to_owned()))
+        } else if let Some(s) = song.as_any().downcast_ref::<UntaggedFileSong>() {
+            (Self::key_from_path(s.path()), Some(s.path().to_owned()))
+        } else {
+            return None;
+        };
+        let display = song.as_display();
+        let title = display.title().to_owned();
+        let artist = display.artist().map(String::from);
+        Some(Self {
+            title: title.clone(),
+            id: key.clone(),
+            artist_name: artist.clone(),
+            info: MusiSongInfo {
+                titles: vec![title],
+                video_id: key,
+                duration: None,
+                tags: vec![],
+                thumbnail_url: "".into(),
+                album: display.album().map(String::from),
+                artist_names: artist.into_iter().collect(),
+                channel_id: "".into(),
+                uploader_id: "".into(),
+            },
+            last_known_path: path,
+        })
+    }
+
+    fn key_from_path(path: &str) -> String {
+        Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_owned())
+    }
+
+    fn video_id(&self) -> &str {
+        if self.info.video_id.is_empty() {
+            &self.id
+        } else {
+            &self.info.video_id
+        }
+    }
+
+    /// songs that are still present on the disk are imported as TaggedFileSong, everything else as YtSong
+    fn into_song(self) -> Song {
+        if let Some(path) = self.last_known_path.as_ref().filter(|p| Path::new(p).exists()) {
+            match TaggedFileSong::from_file_path(path.as_str().into()) {
+                Ok(Some(song)) => return song.into(),
+                Ok(None) => (),
+                Err(err) => error!("{err}"),
+            }
+        }
+        let id = self.video_id().to_owned();
+        let title = Some(self.title)
+        .filter(|t| !t.is_empty())
+        .or(self.info.titles.into_iter().next())
+        .unwrap_or_default();
+        let artist = self.artist_name
+        .or(self.info.artist_names.into_iter().next())
+        .unwrap_or_default();
+        YtSong {
+            title,
+            artist,
+            album: self.info.album,
+            id,
+        }.into()
+    }
+}
+
+#[derive(Debug)]
+struct ImportedList {
+    name: String,
+    songs: Vec<usize>,
+    current_index: Option<usize>,
+}
+
+/// songs in lists are stored as indices into songs
+#[derive(Debug, Default)]
+struct ImportedDB {
+    songs: Vec<Song>,
+    artists: Vec<ImportedList>,
+    playlists: Vec<ImportedList>,
+    queues: Vec<ImportedList>,
+    video_ids: HashMap<String, usize>,
+}
+impl ImportedDB {
+    fn add_song(&mut self, song: MusiSong) -> usize {
+        if let Some(&i) = self.video_ids.get(song.video_id()) {
+            return i;
+        }
+        self.video_ids.insert(song.video_id().to_owned(), self.songs.len());
+        self.songs.push(song.into_song());
+        self.songs.len()-1
+    }
+
+    fn convert_list(&mut self, list: MusiSongProvider) -> ImportedList {
+        let songs = list.songs
+        .into_iter()
+        .map(|s| self.add_song(s))
+        .collect::<Vec<_>>();
+        let current_index = Some(list.current_index)
+        .filter(|&i| i >= 0 && (i as usize) < songs.len())
+        .map(|i| i as usize);
+        ImportedList {
+            name: list.title,
+            songs,
+            current_index,
+        }
+    }
+}
+
+/// parses and converts the json in a seperate thread, then adds everything to the ContentManager
+pub fn import_action(path: String) -> ContentManagerAction {
+    RustParallelAction::Callback {
+        callback: Box::new(move || {
+            let db = MusimanagerDB::from_path(&path)?.convert();
+            let action = ContentManagerAction::Callback {
+                callback: ImportMusimanagerDB { db }.into(),
+            };
+            Ok(action.into())
+        }),
+    }.into()
+}
+
+/// key used to check if a song is already in the register
+fn song_key(song: &Song) -> Option<String> {
+    if let Some(s) = song.as_any().downcast_ref::<YtSong>() {
+        Some(format!("yt:{}", s.id))
+    } else if let Some(s) = song.as_any().downcast_ref::<TaggedFileSong>() {
+        Some(format!("file:{}", s.path()))
+    } else {
+        None
+    }
+}
+
+#[derive(Debug)]
+struct ImportMusimanagerDB {
+    db: ImportedDB,
+}
+impl ContentManagerCallbackTrait for ImportMusimanagerDB {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let db = self.db;
+
+        let mut known = ch.songs
+        .ids()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .filter_map(|id| song_key(ch.get_song(id)).map(|k| (k, id)))
+        .collect::<HashMap<_, _>>();
+        let mut allocated = vec![];
+        let song_ids = db.songs
+        .into_iter()
+        .map(|song| {
+            let key = song_key(&song);
+            match key.as_ref().map(|k| known.get(k)).flatten() {
+                Some(&id) => id,
+                None => {
+                    let id = ch.alloc_song(song);
+                    allocated.push(id);
+                    key.map(|k| known.insert(k, id));
+                    id
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+        let songs_of = |list: &ImportedList| list.songs.iter().map(|&i| song_ids[i]).collect::<Vec<SongID>>();
+
+        // artists with the same name are merged with the existing ones
+        let ap_id = get_or_add_artist_provider(ch);
+        let mut artists = ch.get_provider(ap_id)
+        .as_provider()
+        .unwrap()
+        .providers()
+        .cloned()
+        .map(|id| (ch.get_provider(id).as_display().get_name().into_owned(), id))
+        .collect::<HashMap<_, _>>();
+        for a in db.artists.iter() {
+            let artist_id = match artists.get(&a.name) {
+                Some(&id) => id,
+                None => {
+                    let id = ch.alloc_content_provider(Artist::new(a.name.clone()).into());
+                    ch.get_provider_mut(ap_id)
+                    .as_provider_mut()
+                    .unwrap()
+                    .add_provider(id);
+                    artists.insert(a.name.clone(), id);
+                    id
+                }
+            };
+            for song_id in songs_of(a) {
+                let artist = ch.get_provider_mut(artist_id).as_song_provider_mut().unwrap();
+                if artist.songs().any(|&s| s == song_id) {
+                    continue;
+                }
+                artist.add_song(song_id);
+                ch.register(song_id); // for being stored in the Artist
+            }
+        }
+        let mut sorted = artists.into_iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|(name, _)| name.to_lowercase());
+        *ch.get_provider_mut(ap_id)
+        .as_provider_mut()
+        .unwrap()
+        .providers_mut() = sorted.into_iter().map(|(_, id)| id).collect();
+
+        let pp_id = get_or_add_playlist_provider(ch);
+        for p in db.playlists.iter() {
+            let mut playlist = Playlist::new(p.name.clone());
+            for song_id in songs_of(p) {
+                ch.register(song_id); // for being stored in the Playlist
+                playlist.add_song(song_id);
+            }
+            let id = ch.alloc_content_provider(playlist.into());
+            ch.get_provider_mut(pp_id)
+            .as_provider_mut()
+            .unwrap()
+            .add_provider(id);
+        }
+
+        // QueueProvider adds new queues to the top, so the last one goes in first
+        let qp_id = ch.get_main_provider().queue_provider;
+        for q in db.queues.iter().rev() {
+            let songs = songs_of(q);
+            songs.iter().cloned().for_each(|id| ch.register(id)); // for being stored in the Queue
+            let id = ch.alloc_content_provider(Queue {
+                songs,
+                name: q.name.clone().into(),
+                index: Default::default(),
+                currently_playing: q.current_index,
+                source_cp: qp_id,
+                play_mode: Default::default(),
+                shuffle_order: Default::default(),
+            }.into());
+            // there is no provider these queues were made from
+            ch.get_provider_mut(id)
+            .as_any_mut()
+            .downcast_mut::<Queue>()
+            .unwrap()
+            .source_cp = id;
+            ch.get_queue_provider_mut()
+            .add_queue(id)
+            .map(|id| ch.unregister(id));
+        }
+
+        // every new song is now held by some provider
+        allocated.into_iter().for_each(|id| ch.unregister(id));
+
+        debug!("imported {} songs from musimanager", song_ids.len());
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+pub fn export_action(path: String) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: ExportMusimanagerDB { path }.into(),
+    }
+}
+
+/// the db is built on the main thread (it needs the registers), but written to the disk in a seperate thread
+#[derive(Debug)]
+struct ExportMusimanagerDB {
+    path: String,
+}
+impl ContentManagerCallbackTrait for ExportMusimanagerDB {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let db = MusimanagerDB::from_content_manager(ch);
+        let path = self.path;
+        ContentManagerAction::from(RustParallelAction::Callback {
+            callback: Box::new(move || {
+                db.save_to_path(&path)?;
+                debug!("exported musimanager db to {path}");
+                Ok(ContentManagerAction::None.into())
+            }),
+        }).apply(ch)?;
+        Ok(())
+    }
 }
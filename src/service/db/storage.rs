@@ -0,0 +1,199 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use anyhow::{
+    Result,
+    bail,
+    Context,
+};
+
+use crate::service::db::handler::DBHandler;
+
+/// how the db is stored on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DBFormat {
+    Yaml,
+    /// a lot smaller and faster than yaml for big libraries, but not readable
+    Msgpack,
+}
+
+impl Default for DBFormat {
+    fn default() -> Self {
+        Self::Yaml
+    }
+}
+
+impl DBFormat {
+    pub fn storage(self) -> &'static dyn DBStorage {
+        match self {
+            Self::Yaml => &YamlStorage,
+            Self::Msgpack => &MsgpackStorage,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Msgpack => "msgpack",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "msgpack" | "mpk" => Some(Self::Msgpack),
+            _ => None,
+        }
+    }
+}
+
+/// decoding gives the raw document instead of a DBHandler, so that older dbs can be migrated before they are parsed
+pub trait DBStorage {
+    fn encode(&self, db: &DBHandler) -> Result<Vec<u8>>;
+    fn decode(&self, bytes: &[u8]) -> Result<Value>;
+}
+
+pub struct YamlStorage;
+impl DBStorage for YamlStorage {
+    fn encode(&self, db: &DBHandler) -> Result<Vec<u8>> {
+        Ok(serde_yaml::to_vec(db)?)
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        Ok(serde_yaml::from_slice(bytes)?)
+    }
+}
+
+/// structs are written as maps (with the field names), so the documents look the same as the yaml ones to the migrations
+pub struct MsgpackStorage;
+impl DBStorage for MsgpackStorage {
+    fn encode(&self, db: &DBHandler) -> Result<Vec<u8>> {
+        Ok(rmp_serde::to_vec_named(db)?)
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+/// the formats are picked using the extensions of the paths. older dbs are upgraded on the way
+pub fn convert(from: &Path, to: &Path) -> Result<()> {
+    let format_of = |path: &Path| {
+        DBFormat::from_path(path)
+        .with_context(|| format!("unknown db format of {} (use .yaml or .msgpack)", path.to_string_lossy()))
+    };
+    let (from_format, to_format) = (format_of(from)?, format_of(to)?);
+    if to.exists() {
+        bail!("{} already exists", to.to_string_lossy());
+    }
+    let db = DBHandler::read(from, from_format)?;
+    let bytes = to_format.storage().encode(&db)?;
+    std::fs::write(to, bytes)
+    .with_context(|| format!("could not write db {}", to.to_string_lossy()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use crate::{
+        content::{
+            providers::{
+                ContentProvider,
+                main_provider::MainProvider,
+                playlist::Playlist,
+                traits::SongProvider,
+            },
+            register::{
+                ContentRegister,
+                ContentProviderID,
+                SongID,
+            },
+            song::{
+                Song,
+                tagged_file_song::TaggedFileSong,
+            },
+        },
+        service::{
+            db::migrations::DB_VERSION,
+            editors::EditManager,
+        },
+    };
+
+    /// cargo test --release bench_50k_songs -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_50k_songs() {
+        bench(50_000).unwrap().iter().for_each(|line| println!("{line}"));
+    }
+
+    /// saves and loads a db with this many songs in every format. returns a line for each format
+    fn bench(num_songs: usize) -> Result<Vec<String>> {
+        let db = bench_db(num_songs);
+        let mut lines = vec![];
+        for format in [DBFormat::Yaml, DBFormat::Msgpack] {
+            let path = std::env::temp_dir().join(format!("musiman-bench-db.{}", format.extension()));
+
+            let start = Instant::now();
+            let bytes = format.storage().encode(&db)?;
+            std::fs::write(&path, &bytes)?;
+            let save = start.elapsed();
+
+            let start = Instant::now();
+            DBHandler::read(&path, format)?;
+            let load = start.elapsed();
+            std::fs::remove_file(&path)?;
+
+            lines.push(format!(
+                "{format:?}: {} KiB, save {save:.2?}, load {load:.2?}",
+                bytes.len() / 1024,
+            ));
+        }
+        Ok(lines)
+    }
+
+    /// the songs are all in one playlist
+    fn bench_db(num_songs: usize) -> DBHandler {
+        let mut songs: ContentRegister<Song, SongID> = ContentRegister::new();
+        let mut content_providers: ContentRegister<ContentProvider, ContentProviderID> = ContentRegister::new();
+        let main_provider = {
+            let cp = &mut content_providers;
+            let mut ids = vec![];
+            let main_provider = MainProvider::new(|item| cp.alloc(item), |id| ids.push(id));
+            ids.into_iter().for_each(|id| cp.register(id));
+            cp.alloc(main_provider.into())
+        };
+
+        let mut playlist = Playlist::new("bench");
+        for i in 0..num_songs {
+            let (artist, album) = (i / 100, i / 10);
+            let song = TaggedFileSong::new(
+                format!("/music/artist {artist}/album {album}/song {i}.mp3"),
+                format!("song {i}"),
+                Some(format!("album {album}")),
+                Some(format!("artist {artist}")),
+            );
+            playlist.add_song(songs.alloc(song.into()));
+        }
+        content_providers.alloc(playlist.into());
+
+        DBHandler {
+            version: DB_VERSION,
+            songs,
+            content_providers,
+            main_provider,
+            edit_manager: EditManager::new(),
+            volume: Default::default(),
+            session: None,
+            recovered_from: None,
+            migrated_from: None,
+        }
+    }
+}
@@ -32,6 +32,10 @@ use crate::{
             manager::ContentManager,
         },
     },
+    service::tag_editor::{
+        TagChange,
+        WriteTags,
+    },
 };
 
 
@@ -196,7 +200,7 @@ impl Yanker {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EditManager {
     pub yanker: Option<Yanker>,
 
@@ -297,6 +301,10 @@ pub enum Edit {
         from: String,
         to: String,
     },
+    /// tags written to files. the songs are held by the edit
+    TagEdit {
+        changes: Vec<TagChange>,
+    },
 }
 
 impl Edit {
@@ -331,6 +339,11 @@ impl Edit {
                 ].into()
             },
             Self::TextEdit { content, from, to } => todo!(),
+            Self::TagEdit { changes } => {
+                ContentManagerAction::Callback {
+                    callback: WriteTags { changes: changes.clone(), undo: true }.into(),
+                }.into()
+            }
         }
     }
 
@@ -365,6 +378,11 @@ impl Edit {
                 ].into()
             },
             Self::TextEdit { content, from, to } => todo!(),
+            Self::TagEdit { changes } => {
+                ContentManagerAction::Callback {
+                    callback: WriteTags { changes: changes.clone(), undo: false }.into(),
+                }.into()
+            }
         }
     }
 
@@ -381,6 +399,11 @@ impl Edit {
                 .collect()
             }
             Self::TextEdit { content, from, to } => todo!(),
+            Self::TagEdit { changes } => {
+                changes.iter()
+                .map(|c| c.song.into())
+                .collect()
+            }
         };
         ContentManagerAction::Unregister { ids }.into()
     }
@@ -418,6 +441,9 @@ impl Edit {
                 unreachable!();
             }
             Self::TextEdit { content, from, to } => todo!(),
+            Self::TagEdit { .. } => { // written in TagPreview before the edit is pushed
+                unreachable!();
+            }
         }
     }
 }
@@ -560,6 +586,7 @@ impl YankAction {
             }
             Self::PushEdit { edit } => {
                 ch.edit_manager.edit_stack.push(edit);
+                ch.request_save();
             }
             Self::ClearUndoStack => {
                 if ch.edit_manager.undo_stack.len() > 0 {
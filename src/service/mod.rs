@@ -7,4 +7,6 @@ pub mod notifier;
 pub mod log;
 pub mod python;
 pub mod config;
+pub mod watcher;
+pub mod tag_editor;
 
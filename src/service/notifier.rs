@@ -1,16 +1,208 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
 
+use std::time::{
+    Duration,
+    Instant,
+};
+use chrono::{
+    DateTime,
+    Local,
+};
+use tui::{
+    text::Span,
+    style::{
+        Style,
+        Color,
+    },
+};
+
+use crate::{
+    content::{
+        stack::StateContext,
+        manager::action::ContentManagerAction,
+    },
+    app::{
+        app::SelectedIndex,
+        display::{
+            ListBuilder,
+            SelectedText,
+            Item,
+            Line,
+        },
+    },
+};
+
+/// how long the latest notification stays in the status bar
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+/// older notifications are dropped
+const MAX_NOTIFICATIONS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+impl Severity {
+    pub fn style(&self) -> Style {
+        match self {
+            Self::Info => Style::default().fg(Color::Rgb(100, 200, 100)),
+            Self::Warning => Style::default().fg(Color::Rgb(200, 200, 0)),
+            Self::Error => Style::default().fg(Color::Rgb(220, 60, 60)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    pub time: DateTime<Local>,
+    instant: Instant,
+}
+impl Notification {
+    pub fn new<T: Into<String>>(severity: Severity, message: T) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            time: Local::now(),
+            instant: Instant::now(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        format!(
+            "[{time}] {severity:?}: {message}",
+            time = self.time.format("%H:%M:%S"),
+            severity = self.severity,
+            message = self.message,
+        )
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum NotifierMenuOption {
+    CLEAR_ALL,
+}
 
 pub struct Notifier {
-    // TODO:
-    // use some kinda queue. maybe a channel?
-    pub notifs: Vec<String>,
+    /// oldest first
+    notifs: Vec<Notification>,
+    selected: SelectedIndex,
 }
 
 impl Notifier {
     pub fn new() -> Self {
         Self {
             notifs: vec![],
+            selected: Default::default(),
+        }
+    }
+
+    pub fn notify(&mut self, notif: Notification) {
+        match notif.severity {
+            Severity::Error => error!("{}", notif.message),
+            _ => debug!("{}", notif.message),
+        }
+        self.notifs.push(notif);
+        if self.notifs.len() > MAX_NOTIFICATIONS {
+            self.notifs.remove(0);
         }
     }
-}
 
+    /// the latest notification if it is recent enough
+    pub fn toast(&self) -> Option<&Notification> {
+        self.notifs
+        .last()
+        .filter(|n| n.instant.elapsed() < TOAST_DURATION)
+    }
+
+    pub fn get_selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.selected
+    }
+
+    pub fn selection_increment(&mut self) -> bool {
+        let i = self.selected.selected_index();
+        if i+1 < self.notifs.len() {
+            self.selected.select(i+1);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn selection_decrement(&mut self) -> bool {
+        let i = self.selected.selected_index();
+        if i > 0 {
+            self.selected.select(i-1);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn menu(&self, _: &StateContext) -> Box<dyn Iterator<Item = NotifierMenuOption>> {
+        Box::new([
+            NotifierMenuOption::CLEAR_ALL,
+        ].into_iter())
+    }
+
+    pub fn num_options(&self, ctx: &StateContext) -> usize {
+        self.menu(ctx).count()
+    }
+
+    pub fn apply_option(&mut self, ctx: &mut StateContext) -> ContentManagerAction {
+        let option = self.menu(ctx).skip(ctx.last().selected_index()).next().unwrap();
+        match option {
+            NotifierMenuOption::CLEAR_ALL => {
+                self.notifs.clear();
+                self.selected.select(0);
+                ContentManagerAction::PopContentStack
+            }
+        }
+    }
+
+    /// newest notifications are shown at the top
+    pub fn display(&self) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw("Notifications"));
+        lb.items = self.notifs
+        .iter()
+        .rev()
+        .map(|n| {
+            let mut item = Item {
+                text: vec![Line::new(Span::raw(n.text()))],
+                selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+            };
+            item.text_style(n.severity.style());
+            item
+        })
+        .collect();
+        lb
+    }
+
+    pub fn display_menu(&self, ctx: &StateContext) -> ListBuilder<'static> {
+        let mut lb = ListBuilder::default();
+        lb.title(Span::raw("Notifications"));
+        lb.items = self.menu(ctx)
+        .map(|o| {
+            format!("{o:#?}")
+            .replace("_", " ")
+            .to_lowercase()
+        })
+        .map(Span::from)
+        .map(Line::new)
+        .map(|line| Item {
+            text: vec![line],
+            selected_text: SelectedText::Style(Style::default().fg(Color::Rgb(200, 200, 0))),
+        })
+        .collect();
+        lb
+    }
+}
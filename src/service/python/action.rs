@@ -17,6 +17,7 @@ use pyo3::{
 use derivative::Derivative;
 use anyhow::{
     Result,
+    anyhow,
 };
 
 use crate::{
@@ -26,6 +27,7 @@ use crate::{
                 ContentManagerAction,
             },
         },
+        providers::downloads::JobID,
     },
     service::{
         python::{
@@ -36,6 +38,10 @@ use crate::{
                 PyCode,
             },
         },
+        notifier::{
+            Notification,
+            Severity,
+        },
     },
 };
 
@@ -49,6 +55,20 @@ pub enum PyAction {
         #[derivative(Debug="ignore")]
         callback: PyCallback,
     },
+    /// like ExecCode, but the code can report how far it is by setting res['progress'] (0 to 1)
+    /// and should stop once res['cancel'] is True. errors are passed to the callback instead of being notified
+    ExecJob {
+        job: JobID,
+        code: PyCode,
+        #[derivative(Debug="ignore")]
+        progress: PyProgressCallback,
+        #[derivative(Debug="ignore")]
+        callback: PyJobCallback,
+    },
+    /// sets res['cancel'] of the job if it is still running
+    CancelJob {
+        job: JobID,
+    },
 }
 impl PyAction {
     pub fn run(&mut self, py: Python, pyd: &Py<PyAny>, pyh: &mut PyHandle) -> Result<()> {
@@ -57,6 +77,10 @@ impl PyAction {
             Self::ExecCode {code, ..} => {
                 code
             }
+            Self::ExecJob {code, ..} => {
+                code
+            }
+            Self::CancelJob {..} => unreachable!(), // PyManager does not run these
         };
         debug!("{}", code.code);
         let dict = match &code.globals {
@@ -75,19 +99,40 @@ impl PyAction {
         dbg!("resolving YTAction", &self);
         let globals = [("res", pyd)].into_py_dict(py);
         let pyd = py.eval("res['data']", Some(globals), None)?.extract::<Py<PyAny>>()?;
-        if py.eval("res['error'] != None", Some(globals), None)?.extract::<bool>()? {
-            let err = py.eval("res['error']", Some(globals), None)?.extract::<String>()?;
-            error!("{err}");
-            return Ok(ContentManagerAction::None); // ?
-        }
+        let err = if py.eval("res['error'] != None", Some(globals), None)?.extract::<bool>()? {
+            Some(py.eval("res['error']", Some(globals), None)?.extract::<String>()?)
+        } else {
+            None
+        };
         let action = match self {
             Self::ExecCode {callback, ..} => {
+                if let Some(err) = err {
+                    let notif = Notification::new(Severity::Error, err);
+                    return Ok(ContentManagerAction::Notify { notif });
+                }
                 let res = pyd.extract::<String>(py)?;
                 callback(res)?
             }
+            Self::ExecJob {callback, ..} => {
+                let res = match err {
+                    Some(err) => Err(anyhow!(err)),
+                    None => Ok(pyd.extract::<String>(py)?),
+                };
+                callback(res)?
+            }
+            Self::CancelJob {..} => unreachable!(),
         };
         Ok(action)
     }
+
+    pub fn job(&self) -> Option<JobID> {
+        match self {
+            Self::ExecJob {job, ..} => Some(*job),
+            _ => None,
+        }
+    }
 }
 
 pub type PyCallback = Box<dyn FnOnce(String) -> Result<ContentManagerAction> + Send + Sync>;
+pub type PyJobCallback = Box<dyn FnOnce(Result<String>) -> Result<ContentManagerAction> + Send + Sync>;
+pub type PyProgressCallback = Box<dyn Fn(f64) -> ContentManagerAction + Send + Sync>;
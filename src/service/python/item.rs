@@ -153,6 +153,60 @@ impl PyItem for Ytdl {
     }
 }
 
+/// a separate YoutubeDL instance for downloads, so that extracting info never writes thumbnails
+#[derive(Clone, Debug)]
+pub struct YtdlDownloader {
+    name: Cow<'static, str>,
+}
+impl YtdlDownloader {
+    pub fn new<T: Into<Cow<'static, str>>>(name: T) -> Self {
+        Self { name: name.into() }
+    }
+}
+impl PyItem for YtdlDownloader {
+    fn get_item(&self, py: Python) -> Result<Py<PyAny>> {
+        let ext = config().prefered_song_ext.as_str();
+        let path = config().music_path.join("%(id)s.%(ext)s");
+        let path = path.to_str().unwrap();
+        let code = format!("{{
+            'format': 'bestaudio',
+            'writethumbnail': True,
+            'postprocessors': [
+                {{
+                    'key': 'FFmpegExtractAudio',
+                    'preferredcodec': '{ext}',
+                }},
+                {{
+                    'key': 'FFmpegMetadata',
+                    'add_metadata': True,
+                }},
+                {{
+                    'key': 'EmbedThumbnail',
+                    'already_have_thumbnail': False,
+                }},
+            ],
+            'noplaylist': True,
+            'quiet': True,
+            'outtmpl': {path:?},
+            'verbose': False,
+            'no_warnings': True,
+            'noprogress': True,
+            'geo_bypass': True,
+        }}");
+        let ytdl = py.import("yt_dlp")?
+        .getattr("YoutubeDL")?
+        .call1((py.eval(&code, None, None)?,))?
+        .extract()?;
+        Ok(ytdl)
+    }
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+    fn get_name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Json {
     name: Cow<'static, str>,
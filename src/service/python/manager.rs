@@ -37,11 +37,17 @@ use crate::{
     content::manager::{
         action::ContentManagerAction,
     },
-    service::python::{
-        action::PyAction,
-        item::{
-            PyHandle,
-            Time,
+    service::{
+        python::{
+            action::PyAction,
+            item::{
+                PyHandle,
+                Time,
+            },
+        },
+        notifier::{
+            Notification,
+            Severity,
         },
     },
 };
@@ -50,6 +56,8 @@ use crate::{
 pub struct PyActionEntry {
     action: PyAction,
     pyd: Py<PyAny>,
+    /// the last progress sent for this job
+    progress: Option<f64>,
 }
 
 #[derive(Debug)]
@@ -116,17 +124,39 @@ impl PyManager {
                 // sleeping in python seems to not ruin speed. sleeping in rust somehow destroys it
                 py.run("time.sleep(0.2)", Some(pyh.get_dict(py, &time)?), None)?;
                 match receiver.try_recv() {
+                    Ok(PyAction::CancelJob { job }) => {
+                        if let Some(a) = actions.iter().find(|a| a.action.job() == Some(job)) {
+                            py.run("a['cancel'] = True", Some([("a", &a.pyd),].into_py_dict(py)), None)?;
+                        }
+                    }
                     Ok(a) => {
                         // choosing the default value of a dict so that the new data can be inserted into this dict, and
                         // the memory location does not change. res = data changes the memory location something something
                         // but res['data'] = data does what i want
-                        let pyd = py.eval("{'data': None, 'found': False, 'error': None}", None, None)?.extract()?;
-                        let entry = PyActionEntry {action: a, pyd };
+                        let pyd = py.eval("{'data': None, 'found': False, 'error': None, 'progress': None, 'cancel': False}", None, None)?.extract()?;
+                        let entry = PyActionEntry {action: a, pyd, progress: None };
                         actions.push(entry);
                         let a = actions.last_mut().unwrap();
-                        a.action.run(py, &a.pyd, pyh)?;
+                        if let Err(err) = a.action.run(py, &a.pyd, pyh) {
+                            let _ = actions.pop();
+                            let notif = Notification::new(Severity::Error, format!("{err}"));
+                            sender.send(ContentManagerAction::Notify { notif })?;
+                        }
                     }
                     Err(mpsc::TryRecvError::Empty) => {
+                        for a in actions.iter_mut() {
+                            let progress = match &a.action {
+                                PyAction::ExecJob { progress, .. } => progress,
+                                _ => continue,
+                            };
+                            let p = py
+                            .eval("a['progress']", Some([("a", &a.pyd),].into_py_dict(py)), None)?
+                            .extract::<Option<f64>>()?;
+                            if p.is_some() && p != a.progress {
+                                a.progress = p;
+                                sender.send(progress(p.unwrap()))?;
+                            }
+                        }
                         loop {
                             match actions
                             .iter()
@@ -142,7 +172,12 @@ impl PyManager {
                             .next() {
                                 Some(i) => {
                                     let a = actions.swap_remove(i);
-                                    let action = a.action.resolve(py, &a.pyd, pyh)?;
+                                    let action = match a.action.resolve(py, &a.pyd, pyh) {
+                                        Ok(action) => action,
+                                        Err(err) => ContentManagerAction::Notify {
+                                            notif: Notification::new(Severity::Error, format!("{err}")),
+                                        },
+                                    };
                                     dbg!("sending action");
                                     sender.send(action)?;
                                     dbg!("action sent");
@@ -0,0 +1,183 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use anyhow::{
+    Result,
+    anyhow,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    content::{
+        manager::{
+            action::ContentManagerAction,
+            manager::ContentManager,
+            callback::ContentManagerCallbackTrait,
+        },
+        providers::tag_preview::TagPreview,
+        register::SongID,
+        song::tagged_file_song::{
+            TaggedFileSong,
+            TagField,
+        },
+    },
+    service::{
+        editors::YankedContent,
+        notifier::Severity,
+    },
+};
+
+/// a change that is applied to every yanked song
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagOp {
+    /// None removes the tag
+    Set {
+        field: TagField,
+        value: Option<String>,
+    },
+    /// sets the track numbers to the order of the songs in their provider (starting at 1)
+    Renumber,
+    Replace {
+        field: TagField,
+        pattern: String,
+        with: String,
+    },
+}
+
+impl TagOp {
+    pub fn field(&self) -> TagField {
+        match self {
+            Self::Set { field, .. } => *field,
+            Self::Renumber => TagField::Track,
+            Self::Replace { field, .. } => *field,
+        }
+    }
+
+    fn new_value(&self, song: &TaggedFileSong, position: usize, regex: Option<&Regex>) -> Option<String> {
+        match self {
+            Self::Set { value, .. } => value.clone(),
+            Self::Renumber => Some((position + 1).to_string()),
+            Self::Replace { field, with, .. } => {
+                song.get_tag(*field)
+                .map(|v| regex.unwrap().replace_all(v, with.as_str()).trim().to_owned())
+                .filter(|v| !v.is_empty())
+            }
+        }
+    }
+}
+
+/// a tag of one song going from one value to another. None means the tag is not there
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagChange {
+    pub song: SongID,
+    pub field: TagField,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// the changes the op would make to the songs (in this order). songs that are not tagged local files,
+/// songs that would not change and changes that are not valid tags are left out
+pub fn changes_for(ch: &ContentManager, songs: &[SongID], op: &TagOp) -> Result<Vec<TagChange>> {
+    let regex = match op {
+        TagOp::Replace { pattern, .. } => Some(Regex::new(pattern)?),
+        _ => None,
+    };
+    let field = op.field();
+    let changes = songs.iter()
+    .enumerate() // before the filter, so renumbered songs keep their position in the list
+    .filter_map(|(i, &id)| ch.get_song(id).as_any().downcast_ref::<TaggedFileSong>().map(|s| (i, id, s)))
+    .map(|(i, id, song)| TagChange {
+        song: id,
+        field,
+        from: song.get_tag(field).map(String::from),
+        to: op.new_value(song, i, regex.as_ref()),
+    })
+    .filter(|c| c.from != c.to)
+    .filter(|c| field.validate(c.to.as_deref()).is_ok())
+    .collect();
+    Ok(changes)
+}
+
+/// writes the changes (or undoes them) and returns the ones that worked
+pub fn write_changes(ch: &mut ContentManager, changes: &[TagChange], undo: bool) -> Vec<TagChange> {
+    let mut done = vec![];
+    let mut failed = 0;
+    for c in changes {
+        let value = if undo { c.from.clone() } else { c.to.clone() };
+        let song = ch.get_song(c.song).as_any().downcast_ref::<TaggedFileSong>().cloned();
+        let res = match song {
+            Some(mut song) => song.set_tag(c.field, value).map(|_| song),
+            None => Err(anyhow!("not a tagged local song")),
+        };
+        match res {
+            Ok(song) => {
+                *ch.get_song_mut(c.song) = song.into();
+                done.push(c.clone());
+            }
+            Err(err) => {
+                error!("could not write tag {:?} of {:?}: {err}", c.field, c.song);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        ch.notify(Severity::Error, format!("could not write the tags of {failed} songs"));
+    }
+    done
+}
+
+/// shows what the op would do to the yanked songs. nothing is written till it is applied from the preview
+pub fn preview_action(op: TagOp) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: PreviewTagEdit { op }.into(),
+    }
+}
+
+#[derive(Debug)]
+struct PreviewTagEdit {
+    op: TagOp,
+}
+impl ContentManagerCallbackTrait for PreviewTagEdit {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let mut items = match ch.edit_manager.yanker.as_ref().map(|y| &y.items) {
+            Some(YankedContent::Songs { items }) => items.clone(),
+            _ => {
+                ch.notify(Severity::Warning, "yank the songs to edit first");
+                return Ok(());
+            }
+        };
+        items.sort_by_key(|y| y.index);
+        let songs = items.into_iter().map(|y| y.item).collect::<Vec<_>>();
+        let changes = changes_for(ch, &songs, &self.op)?;
+        if changes.is_empty() {
+            ch.notify(Severity::Info, "nothing to change");
+            return Ok(());
+        }
+
+        changes.iter().for_each(|c| ch.register(c.song)); // for being stored in the TagPreview
+        let id = ch.alloc_content_provider(TagPreview::new(changes).into());
+        ContentManagerAction::PushToContentStack { id: id.into() }.apply(ch)?;
+        ch.unregister(id); // only the content stack holds it
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+/// for undo/redo of Edit::TagEdit
+#[derive(Debug)]
+pub struct WriteTags {
+    pub changes: Vec<TagChange>,
+    pub undo: bool,
+}
+impl ContentManagerCallbackTrait for WriteTags {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        write_changes(ch, &self.changes, self.undo);
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
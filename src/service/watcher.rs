@@ -0,0 +1,301 @@
+#[allow(unused_imports)]
+use crate::{
+    dbg,
+    debug,
+    error,
+};
+
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+use anyhow::Result;
+use notify::{
+    event::{
+        AccessKind,
+        AccessMode,
+        CreateKind,
+        ModifyKind,
+        RenameMode,
+    },
+    Event,
+    EventKind,
+    RecommendedWatcher,
+    RecursiveMode,
+    Watcher,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::content::{
+    manager::{
+        action::ContentManagerAction,
+        manager::ContentManager,
+        callback::ContentManagerCallbackTrait,
+    },
+    providers::{
+        file_explorer::{
+            FileExplorer,
+            FsEntry,
+        },
+        traits::Loadable,
+    },
+    register::{
+        ContentProviderID,
+        ID,
+    },
+};
+
+/// watches the directories of loaded FileExplorers (non recursively). changes are sent back to the
+/// ContentManager as actions. a directory stops being watched once no loaded FileExplorer shows it
+pub struct FileWatcher {
+    /// None if the watcher could not be started (eg. inotify watch limit). explorers just don't update then
+    watcher: Option<RecommendedWatcher>,
+    watched: HashSet<PathBuf>,
+}
+
+impl FileWatcher {
+    pub fn new(sender: UnboundedSender<ContentManagerAction>) -> Self {
+        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            match res {
+                Ok(event) => {
+                    if let Some(action) = changes(event) {
+                        let _ = sender.send(action);
+                    }
+                }
+                Err(err) => error!("file watcher: {err}"),
+            }
+        });
+        let watcher = match watcher {
+            Ok(w) => Some(w),
+            Err(err) => {
+                error!("could not start file watcher: {err}");
+                None
+            }
+        };
+        Self {
+            watcher,
+            watched: Default::default(),
+        }
+    }
+
+    pub fn watch(&mut self, dir: PathBuf) -> Result<()> {
+        if self.watched.contains(&dir) {
+            return Ok(());
+        }
+        if let Some(w) = self.watcher.as_mut() {
+            w.watch(&dir, RecursiveMode::NonRecursive)?;
+            self.watched.insert(dir);
+        }
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self, dir: &Path) -> Result<()> {
+        if !self.watched.remove(dir) {
+            return Ok(());
+        }
+        if let Some(w) = self.watcher.as_mut() {
+            w.unwatch(dir)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn watch_action(dir: String) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: WatchDir { dir }.into(),
+    }
+}
+
+#[derive(Debug)]
+struct WatchDir {
+    dir: String,
+}
+impl ContentManagerCallbackTrait for WatchDir {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        if let Err(err) = ch.watcher.watch(self.dir.clone().into()) {
+            error!("could not watch {}: {err}", self.dir);
+        }
+        Ok(())
+    }
+}
+
+pub fn unwatch_action(dir: String) -> ContentManagerAction {
+    ContentManagerAction::Callback {
+        callback: UnwatchDirs { dirs: vec![dir] }.into(),
+    }
+}
+
+#[derive(Debug)]
+struct UnwatchDirs {
+    dirs: Vec<String>,
+}
+impl ContentManagerCallbackTrait for UnwatchDirs {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        unwatch_unused(ch, self.dirs)
+    }
+}
+
+/// stops watching the dirs that no loaded FileExplorer shows anymore
+pub fn unwatch_unused(ch: &mut ContentManager, dirs: Vec<String>) -> Result<()> {
+    for dir in dirs {
+        if explorers_showing(ch, &dir).is_empty() {
+            ch.watcher.unwatch(Path::new(&dir))?;
+        }
+    }
+    Ok(())
+}
+
+/// the loaded FileExplorers that show this directory
+fn explorers_showing(ch: &ContentManager, dir: &str) -> Vec<ContentProviderID> {
+    ch.content_providers
+    .ids()
+    .filter(|&id| {
+        ch.get_provider(id)
+        .as_any()
+        .downcast_ref::<FileExplorer>()
+        .map(|fe| fe.is_loaded() && Path::new(fe.path.as_ref()) == Path::new(dir))
+        .unwrap_or(false)
+    })
+    .collect()
+}
+
+/// turns a notify event into the entries that were added to/removed from each directory (a rename can move
+/// a file between directories). this runs in the watcher's thread, so the tags of new files are read here
+fn changes(event: Event) -> Option<ContentManagerAction> {
+    let mut removed = vec![];
+    let mut added = vec![];
+    match event.kind {
+        EventKind::Create(CreateKind::File | CreateKind::Folder | CreateKind::Any)
+        | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+        | EventKind::Access(AccessKind::Close(AccessMode::Write)) => { // tags are only complete once the file is closed
+            added.extend(event.paths);
+        }
+        EventKind::Remove(_)
+        | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            removed.extend(event.paths);
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.into_iter();
+            removed.extend(paths.next());
+            added.extend(paths.next());
+        }
+        _ => return None,
+    }
+
+    let dir_of = |p: &Path| p.parent().and_then(|d| d.to_str()).map(String::from);
+    let mut dirs: HashMap<String, (Vec<String>, Vec<(String, FsEntry)>)> = HashMap::new();
+    for p in removed {
+        if let (Some(dir), Some(path)) = (dir_of(&p), p.to_str()) {
+            dirs.entry(dir).or_default().0.push(path.to_owned());
+        }
+    }
+    for p in added {
+        let entry = match FsEntry::read(&p) {
+            Ok(Some(e)) => e,
+            Ok(None) => continue,
+            Err(err) => {
+                debug!("file watcher could not read {}: {err}", p.to_string_lossy());
+                continue;
+            }
+        };
+        if let (Some(dir), Some(path)) = (dir_of(&p), p.to_str()) {
+            dirs.entry(dir).or_default().1.push((path.to_owned(), entry));
+        }
+    }
+
+    if dirs.is_empty() {
+        return None;
+    }
+    let actions = dirs.into_iter()
+    .map(|(dir, (removed, added))| ContentManagerAction::Callback {
+        callback: DirChanged { dir, removed, added }.into(),
+    })
+    .collect::<Vec<_>>();
+    Some(actions.into())
+}
+
+/// applies the change to every loaded FileExplorer that shows the directory. an added path replaces
+/// the entry with the same path (if any), so rewritten files get their tags updated
+#[derive(Debug)]
+struct DirChanged {
+    dir: String,
+    removed: Vec<String>,
+    added: Vec<(String, FsEntry)>,
+}
+impl ContentManagerCallbackTrait for DirChanged {
+    fn call(self: Box<Self>, ch: &mut ContentManager) -> Result<()> {
+        let explorers = explorers_showing(ch, &self.dir);
+        if explorers.is_empty() {
+            ch.watcher.unwatch(Path::new(&self.dir))?;
+            return Ok(());
+        }
+
+        let stale = self.removed.iter()
+        .chain(self.added.iter().map(|(p, _)| p))
+        .map(String::as_str)
+        .collect::<HashSet<_>>();
+        for &id in explorers.iter() {
+            for old in remove_paths(ch, id, &stale) {
+                ch.unregister(old);
+            }
+            for (_, entry) in self.added.iter() {
+                match entry.clone() {
+                    FsEntry::Song(song) => {
+                        let song_id = ch.alloc_song(song); // for being stored in the FileExplorer
+                        ch.get_provider_mut(id).as_song_provider_mut().unwrap().add_song(song_id);
+                    }
+                    FsEntry::Explorer(fe) => {
+                        let cp_id = ch.alloc_content_provider(fe.into()); // for being stored in the FileExplorer
+                        ch.get_provider_mut(id).as_provider_mut().unwrap().add_provider(cp_id);
+                    }
+                }
+            }
+            let cp = ch.get_provider_mut(id);
+            let len = cp.get_size();
+            let index = cp.get_selected_index_mut();
+            if index.selected_index() >= len {
+                index.select(len.saturating_sub(1));
+            }
+        }
+        ContentManagerAction::RefreshDisplayContent.apply(ch)?;
+        Ok(())
+    }
+}
+
+/// takes the songs and child explorers with these paths out of the explorer. the returned ids still need to be unregistered
+fn remove_paths(ch: &mut ContentManager, id: ContentProviderID, paths: &HashSet<&str>) -> Vec<ID> {
+    let songs = ch.get_provider(id)
+    .as_song_provider()
+    .unwrap()
+    .songs()
+    .cloned()
+    .filter(|&s| ch.get_song(s).file_path().map(|p| paths.contains(p)).unwrap_or(false))
+    .collect::<Vec<_>>();
+    let providers = ch.get_provider(id)
+    .as_provider()
+    .unwrap()
+    .providers()
+    .cloned()
+    .filter(|&p| {
+        ch.get_provider(p)
+        .as_any()
+        .downcast_ref::<FileExplorer>()
+        .map(|fe| paths.contains(fe.path.as_ref()))
+        .unwrap_or(false)
+    })
+    .collect::<Vec<_>>();
+
+    let cp = ch.get_provider_mut(id);
+    cp.as_song_provider_mut().unwrap().songs_mut().retain(|s| !songs.contains(s));
+    cp.as_provider_mut().unwrap().providers_mut().retain(|p| !providers.contains(p));
+    songs.into_iter()
+    .map(Into::into)
+    .chain(providers.into_iter().map(Into::into))
+    .collect()
+}